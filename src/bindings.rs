@@ -0,0 +1,407 @@
+use std::{fmt, mem::take, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tetrs_core::input::Input;
+
+use crate::input::Command;
+
+const MODIFIERS: [(KeyModifiers, &str); 3] = [
+    (KeyModifiers::CONTROL, "ctrl"),
+    (KeyModifiers::ALT, "alt"),
+    (KeyModifiers::SHIFT, "shift"),
+];
+
+// Always quits, whatever the bindings are, so that there is a way out
+pub const INTERRUPT: Key = Key {
+    code: KeyCode::Char('c'),
+    modifiers: KeyModifiers::CONTROL,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Left,
+    Right,
+    Down,
+    Rotate,
+    Drop,
+    Pause,
+    Restart,
+    Quit,
+}
+
+// The keys of every action in single player games, several keys may do the same
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bindings {
+    #[serde(deserialize_with = "keys")]
+    pub left: Vec<Key>,
+    #[serde(deserialize_with = "keys")]
+    pub right: Vec<Key>,
+    #[serde(deserialize_with = "keys")]
+    pub down: Vec<Key>,
+    #[serde(deserialize_with = "keys")]
+    pub rotate: Vec<Key>,
+    #[serde(deserialize_with = "keys")]
+    pub drop: Vec<Key>,
+    #[serde(deserialize_with = "keys")]
+    pub pause: Vec<Key>,
+    #[serde(deserialize_with = "keys")]
+    pub restart: Vec<Key>,
+    #[serde(deserialize_with = "keys")]
+    pub quit: Vec<Key>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            left: vec![Key::new(KeyCode::Left)],
+            right: vec![Key::new(KeyCode::Right)],
+            down: vec![Key::new(KeyCode::Down)],
+            rotate: vec![Key::new(KeyCode::Up)],
+            drop: vec![Key::new(KeyCode::Char('d')), Key::new(KeyCode::Char(' '))],
+            pause: vec![Key::new(KeyCode::Char('p')), Key::new(KeyCode::Esc)],
+            restart: vec![Key::new(KeyCode::Char('r'))],
+            quit: vec![Key::new(KeyCode::Char('q')), INTERRUPT],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &Vec<Key> {
+        match action {
+            Action::Left => &self.left,
+            Action::Right => &self.right,
+            Action::Down => &self.down,
+            Action::Rotate => &self.rotate,
+            Action::Drop => &self.drop,
+            Action::Pause => &self.pause,
+            Action::Restart => &self.restart,
+            Action::Quit => &self.quit,
+        }
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut Vec<Key> {
+        match action {
+            Action::Left => &mut self.left,
+            Action::Right => &mut self.right,
+            Action::Down => &mut self.down,
+            Action::Rotate => &mut self.rotate,
+            Action::Drop => &mut self.drop,
+            Action::Pause => &mut self.pause,
+            Action::Restart => &mut self.restart,
+            Action::Quit => &mut self.quit,
+        }
+    }
+
+    pub fn command(&self, event: &KeyEvent) -> Option<Command> {
+        let key = Key::from_event(event)?;
+
+        Action::ALL
+            .into_iter()
+            .find(|&action| self.get(action).contains(&key))
+            .map(Action::command)
+    }
+
+    // Every action needs a key and a key only ever belongs to one action
+    pub fn validate(&self) -> Result<(), String> {
+        let mut bound: Vec<(Action, Key)> = Vec::new();
+
+        for action in Action::ALL {
+            let keys = self.get(action);
+
+            if keys.is_empty() {
+                return Err(format!("{} needs at least one key", action.name()));
+            }
+
+            for &key in keys {
+                if key == INTERRUPT && action != Action::Quit {
+                    return Err(format!(
+                        "{key} always quits and cannot be bound to {}",
+                        action.name()
+                    ));
+                }
+
+                if let Some((other, _)) = bound.iter().find(|&&(x, y)| x != action && y == key) {
+                    return Err(format!(
+                        "{key} is bound to both {} and {}",
+                        other.name(),
+                        action.name()
+                    ));
+                }
+
+                bound.push((action, key));
+            }
+        }
+
+        Ok(())
+    }
+
+    // The key is taken away from any other action, as long as that action keeps a key
+    pub fn bind(&mut self, action: Action, key: Key, replace: bool) -> Result<(), String> {
+        if key == INTERRUPT {
+            return Err(format!("{key} always quits"));
+        }
+
+        if let Some(other) = Action::ALL
+            .into_iter()
+            .find(|&x| x != action && *self.get(x) == [key])
+        {
+            return Err(format!("{key} is the only key of {}", other.name()));
+        }
+
+        for other in Action::ALL {
+            self.get_mut(other).retain(|&x| x != key);
+        }
+
+        let keys = self.get_mut(action);
+
+        if replace {
+            keys.clear();
+        }
+
+        keys.push(key);
+
+        Ok(())
+    }
+
+    // Default keys that are the only key of another action stay there
+    pub fn reset(&mut self, action: Action) {
+        let previous = take(self.get_mut(action));
+
+        for &key in Self::default().get(action) {
+            if key == INTERRUPT {
+                self.get_mut(action).push(key);
+            } else {
+                self.bind(action, key, false).ok();
+            }
+        }
+
+        if self.get(action).is_empty() {
+            *self.get_mut(action) = previous;
+        }
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Left,
+        Action::Right,
+        Action::Down,
+        Action::Rotate,
+        Action::Drop,
+        Action::Pause,
+        Action::Restart,
+        Action::Quit,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Down => "Down",
+            Action::Rotate => "Rotate",
+            Action::Drop => "Drop",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::Quit => "Quit",
+        }
+    }
+
+    const fn command(self) -> Command {
+        match self {
+            Action::Left => Command::Game(Input::Left),
+            Action::Right => Command::Game(Input::Right),
+            Action::Down => Command::Game(Input::Down),
+            Action::Rotate => Command::Game(Input::Rotate),
+            Action::Drop => Command::Game(Input::Drop),
+            Action::Pause => Command::Pause,
+            Action::Restart => Command::Restart,
+            Action::Quit => Command::Quit,
+        }
+    }
+}
+
+impl Key {
+    pub const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    // Shift is already part of characters, e.g. D instead of d. Keys without a name are ignored.
+    pub fn from_event(event: &KeyEvent) -> Option<Self> {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        if !matches!(event.code, KeyCode::Char(_)) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+
+        code_name(event.code)?;
+
+        Some(Self {
+            code: event.code,
+            modifiers,
+        })
+    }
+
+    // The arrows are shown as symbols in the help
+    pub fn label(&self) -> String {
+        let symbol = match self.code {
+            KeyCode::Left => "←",
+            KeyCode::Right => "→",
+            KeyCode::Up => "↑",
+            KeyCode::Down => "↓",
+            _ => return self.to_string(),
+        };
+
+        let mut label = self.to_string();
+        label.truncate(label.rfind('+').map_or(0, |x| x + 1));
+        label.push_str(symbol);
+
+        label
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        write!(f, "{}", code_name(self.code).unwrap_or_default())
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    // Modifiers come first, e.g. ctrl+c or ctrl++ for the plus key
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, name) = match s.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None => match s.rsplit_once('+') {
+                Some((prefix, name)) if !name.is_empty() => (prefix, name),
+                _ => ("", s),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+
+        for part in prefix.split('+').filter(|x| !x.is_empty()) {
+            let (modifier, _) = MODIFIERS
+                .iter()
+                .find(|(_, name)| part.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("Unknown modifier {part} in {s}"))?;
+
+            modifiers |= *modifier;
+        }
+
+        let code = parse_code(name).ok_or_else(|| format!("Unknown key {s}"))?;
+
+        // Characters already tell whether shift was held
+        if let (KeyCode::Char(char), true) = (code, modifiers.contains(KeyModifiers::SHIFT)) {
+            return Err(format!(
+                "{s} never matches, use {} instead",
+                char.to_uppercase()
+            ));
+        }
+
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+fn code_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Left => "left",
+        KeyCode::Right => "right",
+        KeyCode::Up => "up",
+        KeyCode::Down => "down",
+        KeyCode::Char(' ') => "space",
+        KeyCode::Enter => "enter",
+        KeyCode::Esc => "esc",
+        KeyCode::Tab => "tab",
+        KeyCode::Backspace => "backspace",
+        KeyCode::Delete => "delete",
+        KeyCode::Insert => "insert",
+        KeyCode::Home => "home",
+        KeyCode::End => "end",
+        KeyCode::PageUp => "pageup",
+        KeyCode::PageDown => "pagedown",
+        KeyCode::F(number) => return Some(format!("f{number}")),
+        KeyCode::Char(char) => return Some(char.to_string()),
+        _ => return None,
+    };
+
+    Some(name.to_string())
+}
+
+fn parse_code(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(char));
+    }
+
+    let name = name.to_lowercase();
+
+    if let Some(number) = name.strip_prefix('f').and_then(|x| x.parse().ok()) {
+        return Some(KeyCode::F(number));
+    }
+
+    let code = match name.as_str() {
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+// A single key may be given without a list
+fn keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Key>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Names {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let names = match Names::deserialize(deserializer)? {
+        Names::One(name) => vec![name],
+        Names::Many(names) => names,
+    };
+
+    names
+        .iter()
+        .map(|name| name.parse().map_err(de::Error::custom))
+        .collect()
+}
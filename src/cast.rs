@@ -0,0 +1,100 @@
+use std::{
+    cell::RefCell,
+    collections::hash_map::DefaultHasher,
+    fs::File,
+    hash::{Hash, Hasher},
+    io::{self, BufWriter, Write},
+    mem::take,
+    path::Path,
+    rc::Rc,
+};
+
+use serde_json::json;
+use tetrs_core::replay::Replay;
+use tui::{backend::CrosstermBackend, layout::Rect, Terminal, TerminalOptions, Viewport};
+
+use crate::{
+    config::Config,
+    game::{
+        cycle::{GameLoop, VirtualClock},
+        logic::{Autopilot, Logic, TickResult},
+        phase::Phase,
+    },
+    storage,
+    ui::draw_frame,
+};
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+
+// Collects everything the terminal backend writes so it can be stored as one frame
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Output {
+    fn take(&self) -> Vec<u8> {
+        take(&mut self.0.borrow_mut())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Plays the replay through the usual drawing code and writes every changed frame as an event of
+// an asciinema v2 recording
+pub fn export(replay: &Replay, config: &Config, path: &Path) -> io::Result<()> {
+    let output = Output::default();
+    let mut terminal = Terminal::with_options(
+        CrosstermBackend::new(output.clone()),
+        TerminalOptions {
+            viewport: Viewport::fixed(Rect::new(0, 0, WIDTH, HEIGHT)),
+        },
+    )?;
+    let mut file = BufWriter::new(File::create(path)?);
+
+    let header = json!({
+        "version": 2,
+        "width": WIDTH,
+        "height": HEIGHT,
+        "timestamp": storage::timestamp(),
+        "title": format!("tetrs {}", replay.mode.name()),
+    });
+    writeln!(file, "{header}")?;
+
+    let logic = Logic::simulated(replay.state(), Autopilot::Script(replay.script()));
+    let mut game = GameLoop::with_clock(logic, None, VirtualClock::default());
+    let mut previous = None;
+
+    for tick in 0..replay.ticks {
+        let TickResult::Phase(phase) = game.step() else {
+            break;
+        };
+
+        let mut hasher = DefaultHasher::new();
+        phase.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if previous != Some(hash) {
+            previous = Some(hash);
+
+            terminal.draw(|frame| draw_frame(&phase, config, frame))?;
+
+            let time = tick as f64 / replay.tick_rate as f64;
+            let data = String::from_utf8_lossy(&output.take()).into_owned();
+            writeln!(file, "{}", json!([time, "o", data]))?;
+        }
+
+        if matches!(phase, Phase::Finished(_)) {
+            break;
+        }
+    }
+
+    file.flush()
+}
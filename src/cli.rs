@@ -0,0 +1,136 @@
+use std::{path::PathBuf, str::FromStr};
+
+use tetrs_core::mode::Mode;
+
+pub const USAGE: &str = "Command line tetromino game
+
+Usage: tetrs [options] [command]
+
+Commands:
+  play                            Start a game right away instead of the menu
+  replay <file>                   Watch a replay
+  export <replay> [cast]          Export a replay as an asciinema recording
+  scores                          Print the high scores
+  simulate <inputs file | bot>    Play a game without a terminal
+  serve [address]                 Host a game over the network
+  join <address>                  Join a game over the network
+  tbp <command> [arguments...]    Play against a bot speaking the Tetris Bot Protocol
+
+Options:
+  -l, --level <level>      Start level
+  -m, --mode <mode>        marathon, zen, versus or cpu
+  -s, --seed <seed>        Seed of the pieces of single player games
+  -p, --pieces <pieces>    Number of pieces to simulate
+  -c, --config <file>      Config file to use instead of the default one
+      --no-ghost           Hide where the falling piece lands
+      --theme <theme>      Color theme
+  -h, --help               Print the help
+  -V, --version            Print the version";
+
+const COMMANDS: [&str; 7] = [
+    "play", "replay", "export", "scores", "simulate", "serve", "join",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Menu,
+    Play,
+    Replay(String),
+    Export(String, Option<String>),
+    Scores,
+    Simulate(String),
+    Serve(Option<String>),
+    Join(String),
+    Tbp(String, Vec<String>),
+    Help,
+    Version,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Options {
+    pub level: Option<u32>,
+    pub mode: Option<Mode>,
+    pub seed: Option<u64>,
+    pub pieces: Option<u32>,
+    pub config: Option<PathBuf>,
+    pub no_ghost: bool,
+    pub theme: Option<String>,
+}
+
+// Options may come before or after the command, everything after tbp belongs to the bot
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<(Command, Options), String> {
+    let mut args = args.into_iter();
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok((Command::Help, options)),
+            "-V" | "--version" => return Ok((Command::Version, options)),
+            "-l" | "--level" => options.level = Some(value(&arg, args.next())?),
+            "-m" | "--mode" => options.mode = Some(value(&arg, args.next())?),
+            "-s" | "--seed" => options.seed = Some(value(&arg, args.next())?),
+            "-p" | "--pieces" => options.pieces = Some(value(&arg, args.next())?),
+            "-c" | "--config" => options.config = Some(value(&arg, args.next())?),
+            "--no-ghost" => options.no_ghost = true,
+            "--theme" => options.theme = Some(value(&arg, args.next())?),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {arg}")),
+            "tbp" if positional.is_empty() => {
+                let Some(program) = args.next() else {
+                    return Err(String::from("Usage: tetrs tbp <command> [arguments...]"));
+                };
+
+                return Ok((Command::Tbp(program, args.collect()), options));
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let name = positional.next();
+    let first = positional.next();
+    let second = positional.next();
+
+    if let Some(extra) = positional.next() {
+        return Err(format!("Unexpected argument {extra}"));
+    }
+
+    let command =
+        match (name.as_deref(), first, second) {
+            (None, _, _) => Command::Menu,
+            (Some(name), _, _) if !COMMANDS.contains(&name) => {
+                return Err(format!("Unknown command {name}"))
+            }
+            (Some("play"), None, None) => Command::Play,
+            (Some("replay"), Some(file), None) => Command::Replay(file),
+            (Some("replay"), _, _) => return Err(String::from("Usage: tetrs replay <file>")),
+            (Some("export"), Some(replay), cast) => Command::Export(replay, cast),
+            (Some("export"), None, _) => {
+                return Err(String::from(
+                    "Usage: tetrs export <replay file> [cast file]",
+                ))
+            }
+            (Some("scores"), None, None) => Command::Scores,
+            (Some("simulate"), Some(source), None) => Command::Simulate(source),
+            (Some("simulate"), _, _) => return Err(String::from(
+                "Usage: tetrs simulate <inputs file | bot> [--level <level>] [--pieces <pieces>]",
+            )),
+            (Some("serve"), address, None) => Command::Serve(address),
+            (Some("join"), Some(address), None) => Command::Join(address),
+            (Some("join"), _, _) => return Err(String::from("Usage: tetrs join <address>")),
+            (_, first, second) => {
+                let extra = second.or(first).unwrap_or_default();
+                return Err(format!("Unexpected argument {extra}"));
+            }
+        };
+
+    Ok((command, options))
+}
+
+fn value<T: FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("Missing value for {option}"))?;
+
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {value} for {option}"))
+}
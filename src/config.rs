@@ -0,0 +1,274 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, ErrorKind},
+    mem::replace,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use tetrs_core::{
+    mode::Mode,
+    state::{NEXT_PIECES, TICKS_PER_SECOND},
+};
+
+use crate::{
+    bindings::Bindings,
+    cli::Options,
+    theme::{Colors, Theme, BUILT_IN, GUIDELINE},
+};
+
+const FILE: &str = "config.toml";
+const MAX_TICK_RATE: u32 = 240;
+pub const MAX_DAS: u32 = 500;
+pub const MAX_ARR: u32 = 200;
+pub const MAX_LEVEL: u32 = 29;
+// Earlier versions had a single palette in [colors] that belonged to the theme "default"
+const OLD_THEME: &str = "default";
+const MIGRATED_THEME: &str = "custom";
+
+// Settings read from config.toml in the config directory of the platform, e.g. ~/.config/tetrs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub tick_rate: u32,
+    pub level: u32,
+    pub mode: Mode,
+    pub ghost: bool,
+    pub next: usize,
+    pub das: u32,
+    pub arr: u32,
+    pub theme: String,
+    pub grid: bool,
+    pub sound: bool,
+    pub keys: Bindings,
+    pub themes: BTreeMap<String, Theme>,
+    #[serde(skip_serializing)]
+    colors: Option<Theme>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(skip)]
+    replaced: Replaced,
+}
+
+// Values of the file and the options of the command line that replaced them. The file keeps its
+// value when saved, unless the setting was changed in the game.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Replaced {
+    level: Option<(u32, u32)>,
+    mode: Option<(Mode, Mode)>,
+    ghost: Option<(bool, bool)>,
+    theme: Option<(String, String)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tick_rate: TICKS_PER_SECOND,
+            level: 0,
+            mode: Mode::Marathon,
+            ghost: true,
+            next: 1,
+            das: 167,
+            arr: 33,
+            theme: String::from(GUIDELINE),
+            grid: false,
+            sound: false,
+            keys: Bindings::default(),
+            themes: BTreeMap::new(),
+            colors: None,
+            path: None,
+            replaced: Replaced::default(),
+        }
+    }
+}
+
+impl Config {
+    // Without a file every setting has its default, only a file that was asked for has to exist
+    pub fn load(path: Option<PathBuf>) -> Result<Self, String> {
+        let required = path.is_some();
+
+        let Some(path) = path.or_else(default_path) else {
+            return Ok(Self::default());
+        };
+
+        let config = match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound && !required => Self::default(),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            ..config
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+
+        config.migrate();
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // Options of the command line take precedence over the file
+    pub fn apply(mut self, options: &Options) -> Result<Self, String> {
+        if let Some(level) = options.level {
+            self.replaced.level = Some((replace(&mut self.level, level), level));
+        }
+
+        if let Some(mode) = options.mode {
+            self.replaced.mode = Some((replace(&mut self.mode, mode), mode));
+        }
+
+        if options.no_ghost {
+            self.replaced.ghost = Some((replace(&mut self.ghost, false), false));
+        }
+
+        if let Some(theme) = &options.theme {
+            self.replaced.theme = Some((replace(&mut self.theme, theme.clone()), theme.clone()));
+        }
+
+        self.migrate();
+        self.validate()?;
+
+        Ok(self)
+    }
+
+    // Rewrites the whole file, comments in it are lost
+    pub fn save(&self) -> io::Result<()> {
+        let path = self
+            .path
+            .clone()
+            .or_else(default_path)
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "No config directory found"))?;
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let mut file = self.clone();
+        let replaced = self.replaced.clone();
+
+        restore(&mut file.level, replaced.level);
+        restore(&mut file.mode, replaced.mode);
+        restore(&mut file.ghost, replaced.ghost);
+        restore(&mut file.theme, replaced.theme);
+
+        let text = toml::to_string(&file).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+        fs::write(path, text)
+    }
+
+    // The built-in themes come first, then the ones of the config
+    pub fn theme_names(&self) -> Vec<String> {
+        BUILT_IN
+            .iter()
+            .map(|x| x.to_string())
+            .chain(self.themes.keys().cloned())
+            .collect()
+    }
+
+    // The theme is validated, so the fallback only applies to configs that were never checked
+    pub fn colors(&self) -> Colors {
+        self.themes
+            .get(&self.theme)
+            .and_then(Theme::colors)
+            .or_else(|| Colors::built_in(&self.theme))
+            .unwrap_or_default()
+    }
+
+    // A held key moves again after the delay and then repeats at the rate, both in milliseconds
+    pub fn das_ticks(&self) -> u32 {
+        self.ticks(self.das)
+    }
+
+    // Only one input is handled per tick, so the rate cannot be faster than that
+    pub fn arr_ticks(&self) -> u32 {
+        self.ticks(self.arr).max(1)
+    }
+
+    fn ticks(&self, milliseconds: u32) -> u32 {
+        (milliseconds * self.tick_rate + 500) / 1000
+    }
+
+    // Old files keep working and are written in the new form the next time they are saved
+    fn migrate(&mut self) {
+        let colors = self.colors.take();
+
+        if self.theme == OLD_THEME {
+            self.theme = match colors {
+                Some(_) => String::from(MIGRATED_THEME),
+                None => String::from(GUIDELINE),
+            };
+        }
+
+        if let Some(colors) = colors {
+            self.themes
+                .entry(String::from(MIGRATED_THEME))
+                .or_insert(colors);
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.keys.validate().map_err(|e| format!("keys: {e}"))?;
+
+        if !(1..=MAX_TICK_RATE).contains(&self.tick_rate) {
+            return Err(format!("tick_rate must be between 1 and {MAX_TICK_RATE}"));
+        }
+
+        if self.level > MAX_LEVEL {
+            return Err(format!("level must be between 0 and {MAX_LEVEL}"));
+        }
+
+        if self.next > NEXT_PIECES {
+            return Err(format!("next must be between 0 and {NEXT_PIECES}"));
+        }
+
+        if self.das > MAX_DAS {
+            return Err(format!("das must be between 0 and {MAX_DAS}"));
+        }
+
+        if self.arr > MAX_ARR {
+            return Err(format!("arr must be between 0 and {MAX_ARR}"));
+        }
+
+        for (name, theme) in &self.themes {
+            if BUILT_IN.contains(&name.as_str()) {
+                return Err(format!(
+                    "The theme {name} is built in and cannot be replaced"
+                ));
+            }
+
+            if theme.colors().is_none() {
+                return Err(format!(
+                    "Unknown base {} of the theme {name}, choose one of {}",
+                    theme.base,
+                    BUILT_IN.join(", ")
+                ));
+            }
+        }
+
+        if !self.theme_names().contains(&self.theme) {
+            return Err(format!(
+                "Unknown theme {}, choose one of {}",
+                self.theme,
+                self.theme_names().join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("tetrs").join(FILE))
+}
+
+fn restore<T: PartialEq>(value: &mut T, replaced: Option<(T, T)>) {
+    if let Some((file, _)) = replaced.filter(|(_, option)| option == value) {
+        *value = file;
+    }
+}
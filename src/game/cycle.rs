@@ -1,47 +1,124 @@
 use std::time::{Duration, Instant};
 
+use tetrs_core::state::TICKS_PER_SECOND;
+
+use crate::config::Config;
+
 use crate::ui::Ui;
 
 use super::logic::{End, Logic, TickResult};
 
-const TICKS_PER_SECOND: f64 = 60f64;
+pub trait Clock {
+    // Time passed since the clock was created
+    fn now(&self) -> Duration;
+
+    fn sleep(&mut self, duration: Duration);
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn sleep(&mut self, duration: Duration) {
+        if duration > Duration::from_micros(500) {
+            std::thread::sleep(duration - Duration::from_micros(100));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn sleep(&mut self, _: Duration) {
+        // Do nothing because Windows timers are very inaccurate.
+        // This may increase CPU load but does stabilize frame times.
+    }
+}
+
+// A clock that only moves when slept on, so games run as fast as possible and always the same way
+#[derive(Default)]
+pub struct VirtualClock {
+    now: Duration,
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+}
 
-pub struct GameLoop {
+pub struct GameLoop<C: Clock = SystemClock> {
     tick_duration: Duration,
-    previous: Instant,
-    current: Instant,
+    clock: C,
+    previous: Duration,
     accumulated: Duration,
     logic: Logic,
-    ui: Ui,
+    ui: Option<Ui>,
 }
 
 impl GameLoop {
-    pub fn new(logic: Logic, ui: Ui) -> Self {
-        let instant = Instant::now();
+    pub fn new(logic: Logic, ui: Ui, tick_rate: u32) -> Self {
+        Self {
+            tick_duration: Duration::from_secs_f64(1.0 / tick_rate as f64),
+            ..Self::with_clock(logic, Some(ui), SystemClock::default())
+        }
+    }
+}
 
+impl<C: Clock> GameLoop<C> {
+    // Without a ui nothing is drawn
+    pub fn with_clock(logic: Logic, ui: Option<Ui>, clock: C) -> Self {
         GameLoop {
-            tick_duration: Duration::from_secs_f64(1.0 / TICKS_PER_SECOND),
-            previous: instant,
-            current: instant,
+            tick_duration: Duration::from_secs_f64(1.0 / TICKS_PER_SECOND as f64),
+            previous: clock.now(),
+            clock,
             accumulated: Duration::default(),
             logic,
             ui,
         }
     }
 
+    pub fn config(&self) -> &Config {
+        self.logic.config()
+    }
+
     pub fn run(&mut self) -> End {
         loop {
-            if let Some(end) = self.iterate() {
+            if let Some(TickResult::End(end)) = self.iterate() {
                 return end;
             }
         }
     }
 
-    fn iterate(&mut self) -> Option<End> {
-        self.current = Instant::now();
+    // Waits for the next tick and returns its result
+    pub fn step(&mut self) -> TickResult {
+        loop {
+            if let Some(result) = self.iterate() {
+                return result;
+            }
+        }
+    }
+
+    fn iterate(&mut self) -> Option<TickResult> {
+        let current = self.clock.now();
 
-        let mut elapsed = self.current - self.previous;
-        self.previous = self.current;
+        let mut elapsed = current - self.previous;
+        self.previous = current;
 
         if elapsed > self.tick_duration {
             elapsed = self.tick_duration;
@@ -52,29 +129,59 @@ impl GameLoop {
         if self.accumulated >= self.tick_duration {
             self.accumulated -= self.tick_duration;
 
-            match self.logic.update() {
-                TickResult::End(end) => {
-                    return Some(end);
-                }
-                TickResult::Phase(phase) => self.ui.draw(&phase),
-            };
+            let result = self.logic.update();
+
+            if let (TickResult::Phase(phase), Some(ui)) = (&result, &mut self.ui) {
+                ui.draw(phase, self.logic.config());
+            }
+
+            Some(result)
         } else {
-            self.idle(self.tick_duration - self.accumulated);
-        }
+            self.clock.sleep(self.tick_duration - self.accumulated);
 
-        None
+            None
+        }
     }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    fn idle(&self, difference: Duration) {
-        if difference > Duration::from_micros(500) {
-            std::thread::sleep(difference - Duration::from_micros(100));
+#[cfg(test)]
+mod tests {
+    use tetrs_core::{input::Input, mode::Mode, script::Script, state::GameState};
+
+    use crate::game::{logic::Autopilot, phase::Phase};
+
+    use super::*;
+
+    #[test]
+    fn virtual_clock_steps_one_tick_at_a_time() {
+        let state = GameState::seeded(0, Mode::Marathon, 0);
+        let script = Script::new([(0, Input::Drop), (10, Input::Drop)]);
+        let logic = Logic::simulated(state, Autopilot::Script(script));
+        let mut game = GameLoop::with_clock(logic, None, VirtualClock::default());
+
+        for tick in 1..=TICKS_PER_SECOND {
+            let TickResult::Phase(Phase::Running(running)) = game.step() else {
+                panic!("the game ended at tick {tick}");
+            };
+
+            assert_eq!(running.state.time, tick as u64);
+            assert_eq!(running.state.pieces, if tick > 10 { 2 } else { 1 });
+            assert_eq!(game.clock.now(), game.tick_duration * tick);
         }
     }
 
-    #[cfg(target_os = "windows")]
-    fn idle(&self, _: Duration) {
-        // Do nothing because Windows timers are very inaccurate.
-        // This may increase CPU load but does stabilize frame times.
+    #[test]
+    fn script_quits_the_stepped_game() {
+        let state = GameState::seeded(0, Mode::Marathon, 0);
+        let script = Script::parse("5 quit").unwrap();
+        let logic = Logic::simulated(state, Autopilot::Script(script));
+        let mut game = GameLoop::with_clock(logic, None, VirtualClock::default());
+
+        let ticks = std::iter::repeat_with(|| game.step())
+            .take_while(|x| matches!(x, TickResult::Phase(_)))
+            .count();
+
+        assert_eq!(ticks, 5);
+        assert_eq!(game.clock.now(), game.tick_duration * 6);
     }
 }
@@ -1,8 +1,44 @@
-use std::sync::mpsc::Receiver;
+use std::{
+    io::{stdout, Write},
+    sync::mpsc::{self, Receiver},
+};
 
-use crate::input::Input;
+use crossterm::event::Event;
+use tetrs_core::{
+    bot::{Bot, Speed},
+    input::Input,
+    replay::Replay,
+    script::Script,
+    state::GameState,
+};
 
-use super::{phase::menu::Menu, phase::Phase};
+use crate::{
+    bindings::Key,
+    config::Config,
+    history::{Game, History},
+    input::{self, AutoRepeat, Command, Control, Edit, Navigation, Text, VersusInput},
+    net::Connection,
+    scores::{default_name, HighScores, Score},
+    storage::{self, SavedGame},
+    tbp::External,
+};
+
+use super::{
+    phase::Phase,
+    phase::{
+        controls::Controls,
+        finished::Choice,
+        menu::{self, Entry, Menu},
+        online::Online,
+        paused::Paused,
+        playback::Playback,
+        running::Running,
+        scores::Scores,
+        settings::Settings,
+        statistics::Statistics,
+        versus::{Opponent, Player, Versus},
+    },
+};
 
 #[derive(PartialEq)]
 pub enum End {
@@ -15,54 +51,510 @@ pub enum TickResult {
     End(End),
 }
 
-impl From<Input> for Option<End> {
-    fn from(value: Input) -> Self {
-        match value {
-            Input::Restart => Some(End::Restart),
-            Input::Quit => Some(End::Quit),
+impl End {
+    fn from_command(command: Command) -> Option<Self> {
+        match command {
+            Command::Restart => Some(End::Restart),
+            Command::Quit => Some(End::Quit),
             _ => None,
         }
     }
 }
 
+// Plays single player games in place of the keyboard
+pub enum Autopilot {
+    Script(Script),
+    Bot(Bot),
+}
+
+impl Autopilot {
+    fn next_commands(&mut self, state: &GameState) -> Vec<Command> {
+        match self {
+            Autopilot::Script(script) => {
+                let inputs = script.next_inputs();
+                let quit = script.is_over().then_some(Command::Quit);
+
+                inputs.into_iter().map(Command::Game).chain(quit).collect()
+            }
+            Autopilot::Bot(bot) => bot
+                .next_input(state)
+                .map(Command::Game)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
 pub struct Logic {
-    inputs: Receiver<Input>,
+    events: Receiver<Event>,
     phase: Phase,
+    connection: Option<Connection>,
+    external: Option<External>,
+    autopilot: Option<Autopilot>,
+    recording: Option<Replay>,
+    scores: Option<HighScores>,
+    // Only games of players are kept, not the ones of simulations, replays or opponents
+    player: bool,
+    // The seed of every single player game, which keeps them out of the high scores
+    seed: Option<u64>,
+    config: Config,
+    repeat: Option<AutoRepeat>,
 }
 
 impl Logic {
-    pub const fn new(inputs: Receiver<Input>) -> Self {
+    pub fn new(events: Receiver<Event>, config: Config, seed: Option<u64>) -> Self {
+        let scores = HighScores::load();
+        let saved = storage::load_game();
+        let menu = Menu::new(saved, &config);
+
+        Self {
+            events,
+            phase: Phase::Menu(Box::new(menu)),
+            connection: None,
+            external: None,
+            autopilot: None,
+            recording: None,
+            scores: Some(scores),
+            player: true,
+            seed,
+            config,
+            repeat: None,
+        }
+    }
+
+    // Skips the menu and starts a game of the mode and level of the config
+    pub fn play(events: Receiver<Event>, config: Config, seed: Option<u64>) -> Self {
+        let phase = menu::new_game(config.mode, config.level, Speed::Normal, seed);
+
+        let mut logic = Self::new(events, config, seed);
+        logic.start(phase);
+
+        logic
+    }
+
+    pub fn online(
+        events: Receiver<Event>,
+        config: Config,
+        connection: Connection,
+        level: u32,
+    ) -> Self {
+        let mut phase = Phase::Online(Box::new(Online::new(level)));
+        phase.set_tick_rate(config.tick_rate);
+
+        Self {
+            events,
+            phase,
+            connection: Some(connection),
+            external: None,
+            autopilot: None,
+            recording: None,
+            scores: None,
+            player: false,
+            seed: None,
+            config,
+            repeat: None,
+        }
+    }
+
+    pub fn against(
+        events: Receiver<Event>,
+        config: Config,
+        external: External,
+        level: u32,
+    ) -> Self {
+        let versus = Versus::against_external(level, external.name.clone());
+        let mut phase = Phase::Versus(Box::new(versus));
+        phase.set_tick_rate(config.tick_rate);
+
+        Self {
+            events,
+            phase,
+            connection: None,
+            external: Some(external),
+            autopilot: None,
+            recording: None,
+            scores: None,
+            player: false,
+            seed: None,
+            config,
+            repeat: None,
+        }
+    }
+
+    pub fn replay(events: Receiver<Event>, config: Config, replay: Replay) -> Self {
+        Self {
+            events,
+            phase: Phase::Playback(Box::new(Playback::new(replay))),
+            connection: None,
+            external: None,
+            autopilot: None,
+            recording: None,
+            scores: None,
+            player: false,
+            seed: None,
+            config,
+            repeat: None,
+        }
+    }
+
+    // A single player game without a keyboard, used to run games headless
+    pub fn simulated(state: GameState, autopilot: Autopilot) -> Self {
+        let (_, events) = mpsc::channel();
+        let config = Config::default();
+
         Self {
-            inputs,
-            phase: Phase::Menu(Menu {}),
+            events,
+            phase: Phase::Running(Box::new(Running { state })),
+            connection: None,
+            external: None,
+            autopilot: Some(autopilot),
+            recording: None,
+            scores: None,
+            player: false,
+            seed: None,
+            config,
+            repeat: None,
         }
     }
 
+    // Only for terminals that report when a key is released
+    pub fn repeat_keys(&mut self) {
+        self.repeat = Some(AutoRepeat::default());
+    }
+
     pub fn update(&mut self) -> TickResult {
-        let inputs: Vec<Input> = self.inputs.try_iter().collect();
+        let events: Vec<Event> = self.events.try_iter().collect();
+        let running = matches!(self.phase, Phase::Running(_));
+        let repeating = running && self.repeat.is_some();
+
+        let mut commands: Vec<Command> = events
+            .iter()
+            .filter(|x| !(repeating && input::is_repeat(x)))
+            .filter_map(|x| input::single(x, &self.config.keys))
+            .collect();
+
+        // Keys are followed in every phase, so that a key released while paused is not held on
+        if let Some(repeat) = &mut self.repeat {
+            let (delay, rate) = (self.config.das_ticks(), self.config.arr_ticks());
+            let repeated = repeat.update(&events, &self.config.keys, delay, rate);
 
-        if let Some(end) = self.check_for_end(&inputs) {
+            commands.extend(repeated.filter(|_| running).map(Command::Game));
+        }
+
+        if let (Some(autopilot), Phase::Running(running)) = (&mut self.autopilot, &self.phase) {
+            commands.extend(autopilot.next_commands(&running.state));
+        }
+
+        let inputs: Vec<Input> = commands.iter().filter_map(|x| x.game()).collect();
+
+        // A game is paused as soon as the player switches to another window
+        let focus_lost = events.iter().any(|x| matches!(x, Event::FocusLost));
+
+        // Characters are letters of the initials while they are typed, the keys of the controls
+        // screen edit the bindings and any key is a new binding while one is captured, only ctrl+c
+        // always quits
+        let ends: Vec<Command> = match &self.phase {
+            Phase::Finished(finished) if finished.is_typing() => events
+                .iter()
+                .filter(|x| !input::is_text(x))
+                .filter_map(|x| input::single(x, &self.config.keys))
+                .collect(),
+            Phase::Controls(controls) if controls.capture.is_some() => Vec::new(),
+            Phase::Controls(_) => events
+                .iter()
+                .filter(|x| input::edit(x).is_none())
+                .filter_map(|x| input::single(x, &self.config.keys))
+                .collect(),
+            // A game over the network cannot be restarted on one side only and a replay starts over
+            Phase::Online(_) | Phase::Playback(_) => commands
+                .iter()
+                .copied()
+                .filter(|&x| x != Command::Restart)
+                .collect(),
+            _ => commands.clone(),
+        };
+        let end = match events.iter().any(input::is_interrupt) {
+            true => Some(End::Quit),
+            false => self.check_for_end(&ends),
+        };
+
+        if let Some(end) = end {
+            self.save_game(&end);
+            self.save_recording();
             return TickResult::End(end);
         }
 
         match &mut self.phase {
             Phase::Menu(menu) => {
-                if let Some(running) = menu.handle(&inputs) {
-                    self.phase = Phase::Running(running);
+                let navigation: Vec<Navigation> =
+                    events.iter().filter_map(input::navigation).collect();
+
+                match menu.handle(&navigation, &commands) {
+                    Some(Entry::Resume) => {
+                        if let Some(saved) = menu.saved.take() {
+                            storage::delete_game().ok();
+
+                            // The game continues paused, so that the player can get ready first
+                            let running = Running { state: saved.state };
+                            self.recording = saved.recording;
+                            self.phase = Phase::Paused(Box::new(Paused { running }));
+                        }
+                    }
+                    Some(Entry::Play) => {
+                        let phase = menu.new_game(self.seed);
+                        self.start(phase);
+                    }
+                    Some(Entry::Settings) => {
+                        let settings = Settings::new(self.config.clone());
+                        self.phase = Phase::Settings(Box::new(settings));
+                    }
+                    Some(Entry::Controls) => {
+                        let controls = Controls::new(self.config.keys.clone());
+                        self.phase = Phase::Controls(Box::new(controls));
+                    }
+                    Some(Entry::Scores) => {
+                        let scores = self.scores.clone().unwrap_or_default();
+                        self.phase =
+                            Phase::Scores(Box::new(Scores::new(scores, self.config.tick_rate)));
+                    }
+                    Some(Entry::Statistics) => {
+                        let history = History::load();
+                        self.phase = Phase::Statistics(Box::new(Statistics { history }));
+                    }
+                    Some(Entry::Quit) => return TickResult::End(End::Quit),
+                    _ => (),
+                }
+            }
+            Phase::Settings(settings) => {
+                let navigation: Vec<Navigation> =
+                    events.iter().filter_map(input::navigation).collect();
+                let back = settings.handle(&navigation);
+
+                // Every change is saved right away, also when the game is quit from here
+                if settings.config != self.config {
+                    self.config = settings.config.clone();
+                    self.config.save().ok();
+                }
+
+                if back {
+                    self.phase = self.menu();
+                }
+            }
+            Phase::Scores(scores) => {
+                let navigation: Vec<Navigation> =
+                    events.iter().filter_map(input::navigation).collect();
+
+                if scores.handle(&navigation) {
+                    self.phase = self.menu();
+                }
+            }
+            Phase::Statistics(statistics) => {
+                let navigation: Vec<Navigation> =
+                    events.iter().filter_map(input::navigation).collect();
+
+                if statistics.handle(&navigation, &commands) {
+                    self.phase = self.menu();
+                }
+            }
+            Phase::Controls(controls) => {
+                let edits: Vec<Edit> = events.iter().filter_map(input::edit).collect();
+                let keys: Vec<Key> = events.iter().filter_map(input::key).collect();
+
+                let back = controls.handle(&edits, &keys);
+
+                // Every change is saved right away, since the game may be quit from here. The
+                // bindings are kept even if they cannot be written to the config.
+                if controls.bindings != self.config.keys {
+                    self.config.keys = controls.bindings.clone();
+                    self.config.save().ok();
+                }
+
+                if back {
+                    self.phase = self.menu();
+                }
+            }
+            Phase::Running(running) if commands.contains(&Command::Pause) || focus_lost => {
+                let running = running.as_ref().clone();
+                self.phase = Phase::Paused(Box::new(Paused { running }));
+            }
+            Phase::Paused(paused) => {
+                if commands.contains(&Command::Pause) {
+                    self.phase = Phase::Running(Box::new(paused.running.clone()));
                 }
             }
             Phase::Running(running) => {
-                if let Some(finished) = running.handle(&inputs) {
+                let lines = running.state.level.cleared_lines;
+                let finished = running.handle(&inputs);
+
+                if let Some(recording) = &mut self.recording {
+                    recording.record(&inputs, &running.state);
+                }
+
+                if self.config.sound
+                    && (finished.is_some() || running.state.level.cleared_lines > lines)
+                {
+                    ring_bell();
+                }
+
+                if let Some(mut finished) = finished {
+                    if self.player {
+                        History::append(&Game::new(&finished.state)).ok();
+
+                        let score = Score::new(&finished.state, String::new());
+                        let ranked = match self.seed {
+                            Some(_) => None,
+                            None => self.scores.as_ref().and_then(|x| x.rank(&score)),
+                        };
+
+                        if ranked.is_some() {
+                            finished.initials = Some(String::new());
+                        }
+                    }
+
                     self.phase = Phase::Finished(finished);
+                    self.save_recording();
+                }
+            }
+            Phase::Finished(finished) => {
+                let text: Vec<Text> = events.iter().filter_map(input::text).collect();
+
+                match finished.handle(&text, &commands) {
+                    Some(Choice::Save(initials)) => {
+                        if let Some(scores) = &mut self.scores {
+                            let name = match initials.is_empty() {
+                                true => default_name(),
+                                false => initials,
+                            };
+
+                            finished.record = scores.add(Score::new(&finished.state, name));
+                            scores.save();
+                        }
+                    }
+                    Some(Choice::Retry) => {
+                        let (level, mode) = (finished.state.level.start, finished.state.mode);
+                        let phase = menu::new_game(mode, level, Speed::Normal, self.seed);
+                        self.start(phase);
+                    }
+                    Some(Choice::Menu) => self.phase = self.menu(),
+                    None => (),
+                }
+            }
+            Phase::Versus(versus) => {
+                let mut inputs: Vec<VersusInput> = match versus.opponent {
+                    Opponent::Human => events.iter().filter_map(input::versus).collect(),
+                    // Against a bot there is only one human who plays with the usual keys
+                    _ => inputs
+                        .iter()
+                        .map(|&input| VersusInput {
+                            player: Player::One,
+                            input,
+                        })
+                        .collect(),
+                };
+
+                if let Some(external) = &mut self.external {
+                    if let Some(input) = external.next_input(&versus.boards[1].state) {
+                        inputs.push(VersusInput {
+                            player: Player::Two,
+                            input,
+                        });
+                    }
+
+                    if let Opponent::External { unreachable, .. } = &mut versus.opponent {
+                        *unreachable = external.unreachable;
+                    }
+                }
+
+                versus.handle(&inputs)
+            }
+            Phase::Playback(playback) => {
+                let mut controls: Vec<Control> =
+                    events.iter().filter_map(input::playback).collect();
+
+                if commands.contains(&Command::Restart) {
+                    controls.push(Control::Rewind);
+                }
+
+                playback.handle(&controls)
+            }
+            Phase::Online(online) => {
+                if let Some(connection) = &mut self.connection {
+                    match connection.receive() {
+                        Ok(received) => {
+                            // An opponent that cannot be written to has left as well
+                            let sent = online
+                                .handle(&inputs, &received)
+                                .iter()
+                                .try_for_each(|x| connection.send(x));
+
+                            if sent.is_err() {
+                                online.disconnect();
+                            }
+                        }
+                        Err(_) => online.disconnect(),
+                    }
                 }
             }
-            Phase::Finished(finished) => finished.handle(),
         };
 
         TickResult::Phase(self.phase.clone())
     }
 
-    fn check_for_end(&self, inputs: &[Input]) -> Option<End> {
-        inputs.iter().find_map(|&x| x.into())
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn menu(&self) -> Phase {
+        Phase::Menu(Box::new(Menu::new(storage::load_game(), &self.config)))
+    }
+
+    // Single player games are recorded from their first tick on
+    fn start(&mut self, mut phase: Phase) {
+        phase.set_tick_rate(self.config.tick_rate);
+
+        if let Phase::Running(running) = &phase {
+            self.recording = Some(Replay::new(&running.state));
+        }
+
+        self.phase = phase;
+    }
+
+    // Quitting a running game keeps it, together with its recording, to be resumed later
+    fn save_game(&mut self, end: &End) {
+        if *end != End::Quit || !self.player {
+            return;
+        }
+
+        let state = match &self.phase {
+            Phase::Running(running) => &running.state,
+            Phase::Paused(paused) => &paused.running.state,
+            _ => return,
+        };
+
+        let saved = SavedGame {
+            state: state.clone(),
+            recording: self.recording.clone(),
+        };
+
+        if storage::save_game(&saved).is_ok() {
+            self.recording = None;
+        }
     }
+
+    // Failing to save a replay must not end the game, so errors are ignored
+    fn save_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            storage::save_replay(&recording).ok();
+        }
+    }
+
+    fn check_for_end(&self, commands: &[Command]) -> Option<End> {
+        commands.iter().find_map(|&x| End::from_command(x))
+    }
+}
+
+// Sounds are played by the terminal, which may also flash instead
+fn ring_bell() {
+    let mut stdout = stdout();
+    stdout.write_all(b"\x07").and_then(|_| stdout.flush()).ok();
 }
@@ -1,8 +1,4 @@
 pub mod cycle;
-pub mod level;
 pub mod logic;
-pub mod math;
-pub mod state;
-pub mod tetromino;
 
 pub mod phase;
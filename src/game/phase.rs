@@ -1,24 +1,77 @@
-use std::hash::{Hash, Hasher};
+use std::{
+    hash::{Hash, Hasher},
+    mem::discriminant,
+};
 
-use self::{finished::Finished, menu::Menu, running::Running};
+use self::{
+    controls::Controls, finished::Finished, menu::Menu, online::Online, paused::Paused,
+    playback::Playback, running::Running, scores::Scores, settings::Settings,
+    statistics::Statistics, versus::Versus,
+};
 
+pub mod controls;
 pub mod finished;
 pub mod menu;
+pub mod online;
+pub mod paused;
+pub mod playback;
 pub mod running;
+pub mod scores;
+pub mod settings;
+pub mod statistics;
+pub mod versus;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Phase {
-    Menu(Menu),
+    Menu(Box<Menu>),
     Running(Box<Running>),
+    Paused(Box<Paused>),
     Finished(Box<Finished>),
+    Versus(Box<Versus>),
+    Online(Box<Online>),
+    Playback(Box<Playback>),
+    Statistics(Box<Statistics>),
+    Controls(Box<Controls>),
+    Scores(Box<Scores>),
+    Settings(Box<Settings>),
+}
+
+impl Phase {
+    // Games count their time in the ticks of the rate they are played at
+    pub fn set_tick_rate(&mut self, tick_rate: u32) {
+        let states = match self {
+            Phase::Running(running) => vec![&mut running.state],
+            Phase::Versus(versus) => versus.boards.iter_mut().map(|x| &mut x.state).collect(),
+            Phase::Online(online) => vec![&mut online.local.state],
+            _ => Vec::new(),
+        };
+
+        for state in states {
+            state.tick_rate = tick_rate;
+        }
+    }
 }
 
 impl Hash for Phase {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        discriminant(self).hash(state);
+
         match self {
-            Phase::Menu(_) => u64::MAX.hash(state),
+            Phase::Menu(menu) => menu.hash(state),
             Phase::Running(running) => running.state.current.hash(state),
-            Phase::Finished(finished) => finished.state.current.hash(state),
+            Phase::Paused(paused) => paused.hash(state),
+            Phase::Finished(finished) => {
+                finished.state.current.hash(state);
+                finished.record.hash(state);
+                finished.initials.hash(state);
+            }
+            Phase::Versus(versus) => versus.hash(state),
+            Phase::Online(online) => online.hash(state),
+            Phase::Playback(playback) => playback.hash(state),
+            Phase::Statistics(statistics) => statistics.hash(state),
+            Phase::Controls(controls) => controls.hash(state),
+            Phase::Scores(scores) => scores.hash(state),
+            Phase::Settings(settings) => settings.hash(state),
         }
     }
 }
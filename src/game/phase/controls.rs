@@ -0,0 +1,66 @@
+use crate::{
+    bindings::{Action, Bindings, Key},
+    input::Edit,
+};
+
+// Changes the key bindings, a selected action takes the next key that is pressed
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Controls {
+    pub bindings: Bindings,
+    pub selected: usize,
+    pub capture: Option<Capture>,
+    // Why the last key could not be bound
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capture {
+    Replace,
+    Add,
+}
+
+impl Controls {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            selected: 0,
+            capture: None,
+            error: None,
+        }
+    }
+
+    pub fn action(&self) -> Action {
+        Action::ALL[self.selected]
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn handle(&mut self, edits: &[Edit], keys: &[Key]) -> bool {
+        if let Some(capture) = self.capture {
+            if let Some(&key) = keys.first() {
+                let replace = capture == Capture::Replace;
+
+                self.error = self.bindings.bind(self.action(), key, replace).err();
+                self.capture = None;
+            }
+
+            return false;
+        }
+
+        let count = Action::ALL.len();
+
+        for edit in edits {
+            self.error = None;
+
+            match edit {
+                Edit::Previous => self.selected = (self.selected + count - 1) % count,
+                Edit::Next => self.selected = (self.selected + 1) % count,
+                Edit::Replace => self.capture = Some(Capture::Replace),
+                Edit::Add => self.capture = Some(Capture::Add),
+                Edit::Reset => self.bindings.reset(self.action()),
+                Edit::Back => return true,
+            }
+        }
+
+        false
+    }
+}
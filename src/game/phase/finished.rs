@@ -1,10 +1,59 @@
-use crate::game::state::GameState;
+use tetrs_core::state::GameState;
+
+use crate::input::{Command, Text};
+
+const INITIALS: usize = 3;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Finished {
     pub state: GameState,
+    // The rank in the high-score table if the game made it there
+    pub record: Option<usize>,
+    // The initials typed so far while the game waits for them to enter the high-score table
+    pub initials: Option<String>,
+}
+
+pub enum Choice {
+    Save(String),
+    Retry,
+    Menu,
 }
 
 impl Finished {
-    pub fn handle(&self) {}
+    pub fn new(state: GameState) -> Self {
+        Self {
+            state,
+            record: None,
+            initials: None,
+        }
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.initials.is_some()
+    }
+
+    pub fn handle(&mut self, text: &[Text], commands: &[Command]) -> Option<Choice> {
+        if let Some(initials) = &mut self.initials {
+            for &key in text {
+                match key {
+                    Text::Char(c) if c.is_ascii_alphanumeric() && initials.len() < INITIALS => {
+                        initials.push(c.to_ascii_uppercase())
+                    }
+                    Text::Erase => {
+                        initials.pop();
+                    }
+                    Text::Confirm => return self.initials.take().map(Choice::Save),
+                    _ => (),
+                }
+            }
+
+            return None;
+        }
+
+        if text.contains(&Text::Confirm) {
+            return Some(Choice::Retry);
+        }
+
+        commands.contains(&Command::Mode).then_some(Choice::Menu)
+    }
 }
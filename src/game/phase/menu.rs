@@ -1,21 +1,235 @@
-use crate::{game::state::GameState, input::Input};
+use std::hash::{Hash, Hasher};
 
-use super::running::Running;
+use tetrs_core::{
+    bot::{Bot, Speed},
+    input::Input,
+    mode::Mode,
+    state::GameState,
+};
+
+use crate::{
+    config::{Config, MAX_LEVEL},
+    input::{Command, Navigation},
+    storage::SavedGame,
+};
+
+use super::{running::Running, versus::Versus, Phase};
+
+const DEMO_LEVEL: u32 = 5;
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Menu {}
+pub struct Menu {
+    pub mode: Mode,
+    pub speed: Speed,
+    pub demo: Demo,
+    pub saved: Option<SavedGame>,
+    pub level: u32,
+    pub selected: Entry,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Entry {
+    Resume,
+    Play,
+    Mode,
+    Bot,
+    Level,
+    Settings,
+    Controls,
+    Scores,
+    Statistics,
+    Quit,
+}
+
+// A game played by the bot in the background of the menu
+#[derive(Debug, Clone, PartialEq)]
+pub struct Demo {
+    pub running: Running,
+    bot: Bot,
+}
+
+impl Default for Demo {
+    fn default() -> Self {
+        Self {
+            running: Running {
+                state: GameState::new(DEMO_LEVEL, Mode::Marathon),
+            },
+            bot: Bot::new(Speed::Fast),
+        }
+    }
+}
+
+impl Hash for Menu {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mode.hash(state);
+        self.speed.hash(state);
+        self.demo.running.state.current.hash(state);
+        self.saved.is_some().hash(state);
+        self.level.hash(state);
+        self.selected.hash(state);
+    }
+}
+
+impl Entry {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Entry::Resume => "Resume",
+            Entry::Play => "Play",
+            Entry::Mode => "Mode",
+            Entry::Bot => "Bot",
+            Entry::Level => "Level",
+            Entry::Settings => "Settings",
+            Entry::Controls => "Controls",
+            Entry::Scores => "High scores",
+            Entry::Statistics => "Statistics",
+            Entry::Quit => "Quit",
+        }
+    }
+}
 
 impl Menu {
-    pub fn handle(&self, inputs: &[Input]) -> Option<Box<Running>> {
-        let input = inputs.iter().find_map(|&x| match x {
-            Input::Number(number) => Some(number),
-            _ => None,
-        });
-
-        input.map(|level| {
-            Box::new(Running {
-                state: GameState::new(level),
-            })
-        })
+    pub fn new(saved: Option<SavedGame>, config: &Config) -> Self {
+        // A game cannot change its speed halfway, so it is only resumed at the rate it was started at
+        let saved = saved.filter(|x| x.state.tick_rate == config.tick_rate);
+
+        let selected = match saved {
+            Some(_) => Entry::Resume,
+            None => Entry::Play,
+        };
+
+        Self {
+            mode: config.mode,
+            speed: Speed::Normal,
+            demo: Demo::default(),
+            saved,
+            level: config.level,
+            selected,
+        }
+    }
+
+    // The bot can only be chosen for games against it and a game can only be resumed once
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+
+        if self.saved.is_some() {
+            entries.push(Entry::Resume);
+        }
+
+        entries.extend([Entry::Play, Entry::Mode]);
+
+        if self.mode == Mode::Cpu {
+            entries.push(Entry::Bot);
+        }
+
+        entries.extend([
+            Entry::Level,
+            Entry::Settings,
+            Entry::Controls,
+            Entry::Scores,
+            Entry::Statistics,
+            Entry::Quit,
+        ]);
+
+        entries
+    }
+
+    // Returns the entry the player chose, entries with a value change it instead
+    pub fn handle(&mut self, navigation: &[Navigation], commands: &[Command]) -> Option<Entry> {
+        self.demo.handle();
+
+        for &command in commands {
+            match command {
+                Command::Mode => self.mode = self.mode.next(),
+                Command::Bot => self.speed = self.speed.next(),
+                Command::Statistics => return Some(Entry::Statistics),
+                Command::Controls => return Some(Entry::Controls),
+                Command::Number(digit) => self.type_level(digit),
+                _ => (),
+            }
+        }
+
+        for &step in navigation {
+            let entries = self.entries();
+            let count = entries.len();
+            let index = entries.iter().position(|&x| x == self.selected);
+
+            match (step, self.selected) {
+                (Navigation::Up, _) => {
+                    self.selected = entries[index.map_or(0, |x| (x + count - 1) % count)]
+                }
+                (Navigation::Down, _) => {
+                    self.selected = entries[index.map_or(0, |x| (x + 1) % count)]
+                }
+                (Navigation::Left, entry) => self.change(entry, false),
+                (Navigation::Right | Navigation::Select, entry @ (Entry::Mode | Entry::Bot)) => {
+                    self.change(entry, true)
+                }
+                (Navigation::Right, entry) => self.change(entry, true),
+                (Navigation::Select, Entry::Level) => return Some(Entry::Play),
+                (Navigation::Select, entry) => return Some(entry),
+                (Navigation::Back, _) => (),
+            }
+        }
+
+        None
+    }
+
+    pub fn new_game(&self, seed: Option<u64>) -> Phase {
+        new_game(self.mode, self.level, self.speed, seed)
+    }
+
+    fn change(&mut self, entry: Entry, forward: bool) {
+        let count = MAX_LEVEL + 1;
+
+        match (entry, forward) {
+            (Entry::Mode, true) => self.mode = self.mode.next(),
+            (Entry::Mode, false) => self.mode = self.mode.previous(),
+            (Entry::Bot, true) => self.speed = self.speed.next(),
+            (Entry::Bot, false) => self.speed = self.speed.previous(),
+            (Entry::Level, true) => self.level = (self.level + 1) % count,
+            (Entry::Level, false) => self.level = (self.level + count - 1) % count,
+            _ => (),
+        }
+    }
+
+    // Digits are typed into the level, e.g. 1 and then 5 for level 15
+    fn type_level(&mut self, digit: u32) {
+        let level = self.level * 10 + digit;
+
+        self.level = match level <= MAX_LEVEL && self.selected == Entry::Level {
+            true => level,
+            false => digit,
+        };
+        self.selected = Entry::Level;
+    }
+}
+
+// Only single player games use the seed
+pub fn new_game(mode: Mode, level: u32, speed: Speed, seed: Option<u64>) -> Phase {
+    match mode {
+        Mode::Versus => Phase::Versus(Box::new(Versus::new(level))),
+        Mode::Cpu => Phase::Versus(Box::new(Versus::against_cpu(level, speed))),
+        _ => {
+            let state = match seed {
+                Some(seed) => GameState::seeded(level, mode, seed),
+                None => GameState::new(level, mode),
+            };
+
+            Phase::Running(Box::new(Running { state }))
+        }
+    }
+}
+
+impl Demo {
+    fn handle(&mut self) {
+        let inputs: Vec<Input> = self
+            .bot
+            .next_input(&self.running.state)
+            .into_iter()
+            .collect();
+
+        if self.running.handle(&inputs).is_some() {
+            *self = Self::default();
+        }
     }
 }
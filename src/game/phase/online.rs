@@ -0,0 +1,103 @@
+use std::{
+    hash::{Hash, Hasher},
+    mem::take,
+};
+
+use tetrs_core::{input::Input, mode::Mode, state::GameState};
+
+use crate::net::{Message, Snapshot};
+
+use super::running::Running;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Won,
+    Lost,
+    Disconnected,
+}
+
+impl Outcome {
+    pub const fn text(&self) -> &'static str {
+        match self {
+            Outcome::Won => "you win!",
+            Outcome::Lost => "you lose!",
+            Outcome::Disconnected => "opponent left",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Online {
+    pub local: Running,
+    pub opponent: Option<Snapshot>,
+    pub outcome: Option<Outcome>,
+    sent: Option<Snapshot>,
+}
+
+impl Hash for Online {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.local.state.current.hash(state);
+        self.local.state.garbage.total().hash(state);
+        self.opponent.hash(state);
+        self.outcome.hash(state);
+    }
+}
+
+impl Online {
+    pub fn new(level: u32) -> Self {
+        Self {
+            local: Running {
+                state: GameState::new(level, Mode::Versus),
+            },
+            opponent: None,
+            outcome: None,
+            sent: None,
+        }
+    }
+
+    // Returns the messages that have to be sent to the opponent
+    pub fn handle(&mut self, inputs: &[Input], received: &[Message]) -> Vec<Message> {
+        if self.outcome.is_some() {
+            return Vec::new();
+        }
+
+        for message in received {
+            match message {
+                Message::Board(snapshot) => self.opponent = Some(snapshot.clone()),
+                Message::Garbage(lines) => self.local.state.garbage.receive(*lines),
+                Message::Lost => self.outcome = Some(Outcome::Won),
+                Message::Start(_) => (),
+            }
+        }
+
+        if self.outcome.is_some() {
+            return Vec::new();
+        }
+
+        if self.local.handle(inputs).is_some() {
+            self.outcome = Some(Outcome::Lost);
+            return vec![Message::Lost];
+        }
+
+        let mut messages = Vec::new();
+
+        let garbage = take(&mut self.local.state.outgoing);
+        if garbage > 0 {
+            messages.push(Message::Garbage(garbage));
+        }
+
+        let snapshot = Snapshot::from(&self.local.state);
+        if self.sent.as_ref() != Some(&snapshot) {
+            self.sent = Some(snapshot.clone());
+            messages.push(Message::Board(snapshot));
+        }
+
+        messages
+    }
+
+    pub fn disconnect(&mut self) {
+        if self.outcome.is_none() {
+            self.outcome = Some(Outcome::Disconnected);
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use std::hash::{Hash, Hasher};
+
+use super::running::Running;
+
+// A single player game on hold, the board stays hidden until the game is resumed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paused {
+    pub running: Running,
+}
+
+impl Hash for Paused {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.running.state.current.hash(state);
+    }
+}
@@ -0,0 +1,143 @@
+use std::hash::{Hash, Hasher};
+
+use tetrs_core::{replay::Replay, script::Script};
+
+use crate::input::Control;
+
+use super::running::Running;
+
+// Speeds in quarter ticks per tick, from 0.25x to 8x
+const SPEEDS: [(u32, &str); 6] = [
+    (1, "0.25x"),
+    (2, "0.5x"),
+    (4, "1x"),
+    (8, "2x"),
+    (16, "4x"),
+    (32, "8x"),
+];
+const NORMAL_SPEED: usize = 2;
+
+// Plays a recorded game again, the recorded inputs take the place of the keyboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    replay: Replay,
+    script: Script,
+    tick: u64,
+    quarters: u32,
+    speed: usize,
+    pub running: Running,
+    pub paused: bool,
+    pub over: bool,
+    pub pieces: u32,
+}
+
+impl Hash for Playback {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.running.state.current.hash(state);
+        self.running.state.pieces.hash(state);
+        self.speed.hash(state);
+        self.paused.hash(state);
+        self.over.hash(state);
+    }
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        let pieces = replay.pieces;
+
+        let mut playback = Self {
+            running: Running {
+                state: replay.state(),
+            },
+            script: replay.script(),
+            replay,
+            tick: 0,
+            quarters: 0,
+            speed: NORMAL_SPEED,
+            paused: false,
+            over: false,
+            pieces,
+        };
+
+        // Replays saved before the pieces were recorded are played once to count them
+        if pieces == 0 {
+            let mut ended = playback.clone();
+            while ended.step() {}
+            playback.pieces = ended.running.state.pieces;
+        }
+
+        playback
+    }
+
+    pub fn speed(&self) -> &'static str {
+        SPEEDS[self.speed].1
+    }
+
+    pub fn handle(&mut self, controls: &[Control]) {
+        for control in controls {
+            match control {
+                Control::Pause => self.paused = !self.paused,
+                Control::Faster => self.speed = (self.speed + 1).min(SPEEDS.len() - 1),
+                Control::Slower => self.speed = self.speed.saturating_sub(1),
+                Control::Step => {
+                    self.paused = true;
+                    self.step();
+                }
+                Control::Rewind => {
+                    self.rewind();
+                    self.paused = false;
+                }
+                Control::Seek(pieces) => {
+                    let piece = self.running.state.pieces.saturating_add_signed(*pieces);
+                    self.seek(piece.min(self.pieces));
+                }
+            }
+        }
+
+        if self.paused {
+            return;
+        }
+
+        self.quarters += SPEEDS[self.speed].0;
+
+        while self.quarters >= 4 {
+            self.quarters -= 4;
+            self.step();
+        }
+    }
+
+    // Plays a single tick of the recorded game, returns false once it is over
+    fn step(&mut self) -> bool {
+        if self.over || self.tick >= self.replay.ticks {
+            self.over = true;
+            return false;
+        }
+
+        let inputs = self.script.next_inputs();
+        self.tick += 1;
+
+        if self.running.handle(&inputs).is_some() {
+            self.over = true;
+        }
+
+        !self.over
+    }
+
+    fn rewind(&mut self) {
+        self.running = Running {
+            state: self.replay.state(),
+        };
+        self.script = self.replay.script();
+        self.tick = 0;
+        self.over = false;
+    }
+
+    // Starts over when seeking backwards, then plays until the piece is reached
+    fn seek(&mut self, piece: u32) {
+        if piece < self.running.state.pieces {
+            self.rewind();
+        }
+
+        while self.running.state.pieces < piece && self.step() {}
+    }
+}
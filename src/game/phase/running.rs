@@ -1,9 +1,6 @@
 use std::mem::swap;
 
-use crate::{
-    game::{state::GameState, tetromino::Tetromino},
-    input::Input,
-};
+use tetrs_core::{input::Input, level::ClearedLines, mode::Mode, state::GameState};
 
 use super::finished::Finished;
 
@@ -15,20 +12,46 @@ pub struct Running {
 impl Running {
     pub fn handle(&mut self, inputs: &[Input]) -> Option<Box<Finished>> {
         if self.state.is_finished() {
-            return Some(Box::new(Finished {
-                state: self.state.clone(),
-            }));
+            if !self.state.mode.is_endless() {
+                return Some(Box::new(Finished::new(self.state.clone())));
+            }
+
+            self.state.clear_field();
         }
 
         let solidified = self.handle_inputs(inputs);
         let solidified = self.state.advance_game(solidified);
 
         if solidified {
+            let minimal_inputs = self.state.minimal_inputs();
+            self.state
+                .stats
+                .placed(self.state.current.kind, minimal_inputs);
+
+            let spin = self.state.spin();
             let cleared_lines = self.state.clear_lines();
+            let perfect_clear = self.state.is_perfect_clear();
+
+            let attack = self
+                .state
+                .attack
+                .calculate(&cleared_lines, spin, perfect_clear);
+            self.state.stats.cleared(&cleared_lines, attack);
+            let sent = self.state.garbage.cancel(attack);
+
+            // Only games against an opponent take the garbage that is sent
+            if self.state.mode == Mode::Versus {
+                self.state.outgoing += sent;
+            }
             self.state.level.up(&cleared_lines);
 
+            if matches!(cleared_lines, ClearedLines::None) {
+                self.state.receive_garbage();
+            }
+
             swap(&mut self.state.current, &mut self.state.next);
-            self.state.next = Tetromino::next();
+            self.state.next = self.state.deal_tetromino();
+            self.state.pieces += 1;
         }
 
         self.state.preview = self.state.determine_preview();
@@ -46,6 +69,8 @@ impl Running {
     }
 
     fn handle_input(&mut self, input: &Input) -> bool {
+        self.state.stats.key(input);
+
         match input {
             Input::Right => self.state.move_right(),
             Input::Left => self.state.move_left(),
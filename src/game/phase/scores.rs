@@ -0,0 +1,49 @@
+use std::hash::{Hash, Hasher};
+
+use tetrs_core::mode::Mode;
+
+use crate::{input::Navigation, scores::HighScores};
+
+// Only modes that end keep high scores, zen clears the board instead of topping out
+pub const MODES: [Mode; 1] = [Mode::Marathon];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scores {
+    pub scores: HighScores,
+    pub mode: Mode,
+    pub tick_rate: u32,
+}
+
+// The table does not change while it is shown
+impl Hash for Scores {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mode.hash(state);
+    }
+}
+
+impl Scores {
+    // Only the table of the tick rate of the config is shown
+    pub fn new(scores: HighScores, tick_rate: u32) -> Self {
+        Self {
+            scores,
+            mode: Mode::Marathon,
+            tick_rate,
+        }
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn handle(&mut self, navigation: &[Navigation]) -> bool {
+        for step in navigation {
+            match step {
+                Navigation::Left | Navigation::Right => {
+                    let index = MODES.iter().position(|&x| x == self.mode).unwrap_or(0);
+                    self.mode = MODES[(index + 1) % MODES.len()];
+                }
+                Navigation::Select | Navigation::Back => return true,
+                _ => (),
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,132 @@
+use std::hash::{Hash, Hasher};
+
+use tetrs_core::state::NEXT_PIECES;
+
+use crate::{
+    config::{Config, MAX_ARR, MAX_DAS},
+    input::Navigation,
+};
+
+const DAS_STEP: u32 = 10;
+const ARR_STEP: u32 = 5;
+
+// Changes the settings of the config, the arrows change the selected one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub config: Config,
+    pub selected: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Setting {
+    Ghost,
+    Next,
+    Das,
+    Arr,
+    Theme,
+    Grid,
+    Sound,
+}
+
+impl Hash for Settings {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.selected.hash(state);
+        self.config.ghost.hash(state);
+        self.config.next.hash(state);
+        self.config.das.hash(state);
+        self.config.arr.hash(state);
+        self.config.theme.hash(state);
+        self.config.grid.hash(state);
+        self.config.sound.hash(state);
+    }
+}
+
+impl Setting {
+    pub const ALL: [Setting; 7] = [
+        Setting::Ghost,
+        Setting::Next,
+        Setting::Das,
+        Setting::Arr,
+        Setting::Theme,
+        Setting::Grid,
+        Setting::Sound,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Setting::Ghost => "Ghost piece",
+            Setting::Next => "Next pieces",
+            Setting::Das => "Key delay",
+            Setting::Arr => "Key repeat",
+            Setting::Theme => "Theme",
+            Setting::Grid => "Grid lines",
+            Setting::Sound => "Sound",
+        }
+    }
+}
+
+impl Settings {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            selected: 0,
+        }
+    }
+
+    pub fn setting(&self) -> Setting {
+        Setting::ALL[self.selected]
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn handle(&mut self, navigation: &[Navigation]) -> bool {
+        let count = Setting::ALL.len();
+
+        for step in navigation {
+            match step {
+                Navigation::Up => self.selected = (self.selected + count - 1) % count,
+                Navigation::Down => self.selected = (self.selected + 1) % count,
+                Navigation::Left => self.change(false),
+                Navigation::Right | Navigation::Select => self.change(true),
+                Navigation::Back => return true,
+            }
+        }
+
+        false
+    }
+
+    // Numbers stop at their limits, everything else goes round
+    fn change(&mut self, forward: bool) {
+        let setting = self.setting();
+        let config = &mut self.config;
+
+        match setting {
+            Setting::Ghost => config.ghost = !config.ghost,
+            Setting::Grid => config.grid = !config.grid,
+            Setting::Sound => config.sound = !config.sound,
+            Setting::Next => config.next = step(config.next, forward, NEXT_PIECES + 1),
+            Setting::Das => config.das = clamp(config.das, forward, DAS_STEP, MAX_DAS),
+            Setting::Arr => config.arr = clamp(config.arr, forward, ARR_STEP, MAX_ARR),
+            Setting::Theme => {
+                let names = config.theme_names();
+                let index = names.iter().position(|x| *x == config.theme);
+                let index = step(index.unwrap_or(0), forward, names.len());
+
+                config.theme = names[index].clone();
+            }
+        }
+    }
+}
+
+fn step(index: usize, forward: bool, count: usize) -> usize {
+    match forward {
+        true => (index + 1) % count,
+        false => (index + count - 1) % count,
+    }
+}
+
+fn clamp(value: u32, forward: bool, step: u32, max: u32) -> u32 {
+    match forward {
+        true => (value + step).min(max),
+        false => value.saturating_sub(step),
+    }
+}
@@ -0,0 +1,28 @@
+use std::hash::{Hash, Hasher};
+
+use crate::{
+    history::History,
+    input::{Command, Navigation},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub history: History,
+}
+
+// The history does not change while it is shown
+impl Hash for Statistics {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.history.games.len().hash(state);
+    }
+}
+
+impl Statistics {
+    // Returns true when the player wants to go back to the menu
+    pub fn handle(&self, navigation: &[Navigation], commands: &[Command]) -> bool {
+        navigation.contains(&Navigation::Back)
+            || commands
+                .iter()
+                .any(|x| matches!(x, Command::Statistics | Command::Mode))
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    hash::{Hash, Hasher},
+    mem::take,
+};
+
+use tetrs_core::{
+    bot::{Bot, Speed},
+    input::Input,
+    mode::Mode,
+    state::GameState,
+};
+
+use crate::input::VersusInput;
+
+use super::running::Running;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Player::One => "player one",
+            Player::Two => "player two",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Winner(Player),
+    // Both players topped out in the same tick
+    Draw,
+}
+
+// Who controls player two
+#[derive(Debug, Clone, PartialEq)]
+pub enum Opponent {
+    Human,
+    Cpu(Bot),
+    // A bot running in another process, its inputs are provided from the outside, together with
+    // how many of its suggestions could not be played
+    External { name: String, unreachable: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Versus {
+    pub boards: [Running; 2],
+    pub outcome: Option<Outcome>,
+    pub opponent: Opponent,
+}
+
+impl Hash for Versus {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for board in self.boards.iter() {
+            board.state.current.hash(state);
+            board.state.garbage.total().hash(state);
+        }
+        self.outcome.hash(state);
+    }
+}
+
+impl Versus {
+    pub fn new(level: u32) -> Self {
+        let board = || Running {
+            state: GameState::new(level, Mode::Versus),
+        };
+
+        Self {
+            boards: [board(), board()],
+            outcome: None,
+            opponent: Opponent::Human,
+        }
+    }
+
+    pub fn against_cpu(level: u32, speed: Speed) -> Self {
+        Self {
+            opponent: Opponent::Cpu(Bot::new(speed)),
+            ..Self::new(level)
+        }
+    }
+
+    pub fn against_external(level: u32, name: String) -> Self {
+        Self {
+            opponent: Opponent::External {
+                name,
+                unreachable: 0,
+            },
+            ..Self::new(level)
+        }
+    }
+
+    pub fn handle(&mut self, inputs: &[VersusInput]) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        let mut inputs = inputs.to_vec();
+        if let Opponent::Cpu(bot) = &mut self.opponent {
+            if let Some(input) = bot.next_input(&self.boards[1].state) {
+                inputs.push(VersusInput {
+                    player: Player::Two,
+                    input,
+                });
+            }
+        }
+
+        // Both boards play the tick before either of them can lose
+        let lost: Vec<bool> = self
+            .boards
+            .iter_mut()
+            .zip([Player::One, Player::Two])
+            .map(|(board, player)| {
+                let inputs: Vec<Input> = inputs
+                    .iter()
+                    .filter(|x| x.player == player)
+                    .map(|x| x.input)
+                    .collect();
+
+                board.handle(&inputs).is_some()
+            })
+            .collect();
+
+        self.outcome = match lost[..] {
+            [true, true] => Some(Outcome::Draw),
+            [true, false] => Some(Outcome::Winner(Player::Two)),
+            [false, true] => Some(Outcome::Winner(Player::One)),
+            _ => None,
+        };
+
+        if self.outcome.is_some() {
+            return;
+        }
+
+        let [one, two] = &mut self.boards;
+        two.state.garbage.receive(take(&mut one.state.outgoing));
+        one.state.garbage.receive(take(&mut two.state.outgoing));
+    }
+}
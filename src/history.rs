@@ -0,0 +1,154 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+};
+
+use serde::{Deserialize, Serialize};
+use tetrs_core::{
+    mode::Mode,
+    state::{GameState, TICKS_PER_SECOND},
+};
+
+use crate::storage;
+
+// One line per game so that a broken line only loses that game
+const FILE: &str = "history.jsonl";
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Game {
+    pub mode: Mode,
+    pub date: u64,
+    pub score: u32,
+    pub lines: u32,
+    pub level: u32,
+    pub pieces: u32,
+    pub time: u64,
+    #[serde(default = "ticks_per_second")]
+    pub tick_rate: u32,
+    pub pps: f64,
+    pub apm: f64,
+    pub faults: u32,
+    pub clears: [u32; 4],
+}
+
+impl Game {
+    pub fn new(state: &GameState) -> Self {
+        Self {
+            mode: state.mode,
+            date: storage::timestamp(),
+            score: state.level.score,
+            lines: state.level.cleared_lines,
+            level: state.level.current,
+            pieces: state.pieces,
+            time: state.time,
+            tick_rate: state.tick_rate,
+            pps: state.pieces_per_second(),
+            apm: state.attack_per_minute(),
+            faults: state.stats.faults,
+            clears: state.stats.clears,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct History {
+    pub games: Vec<Game>,
+}
+
+impl History {
+    // A missing file is an empty history
+    pub fn load() -> Self {
+        let games = storage::path(FILE)
+            .and_then(fs::read_to_string)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+
+        Self { games }
+    }
+
+    pub fn append(game: &Game) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(storage::path(FILE)?)?;
+
+        writeln!(file, "{}", serde_json::to_string(game)?)
+    }
+
+    pub fn modes(&self) -> Vec<Mode> {
+        let mut modes: Vec<Mode> = Vec::new();
+        for game in self.games.iter() {
+            if !modes.contains(&game.mode) {
+                modes.push(game.mode);
+            }
+        }
+
+        modes
+    }
+
+    // Every game of the mode that beat all games before it
+    pub fn personal_bests(&self, mode: Mode) -> Vec<&Game> {
+        let mut bests: Vec<&Game> = Vec::new();
+
+        for game in self.games.iter().filter(|x| x.mode == mode) {
+            if bests.last().map_or(true, |best| game.score > best.score) {
+                bests.push(game);
+            }
+        }
+
+        bests
+    }
+
+    // In seconds, as games may have been played at different tick rates
+    pub fn total_time(&self) -> u64 {
+        self.games.iter().map(|x| x.time / x.tick_rate as u64).sum()
+    }
+
+    pub fn total_pieces(&self) -> u64 {
+        self.games.iter().map(|x| x.pieces as u64).sum()
+    }
+
+    pub fn total_lines(&self) -> u64 {
+        self.games.iter().map(|x| x.lines as u64).sum()
+    }
+
+    pub fn average_pps(&self) -> f64 {
+        match self.games.is_empty() {
+            true => 0.0,
+            false => self.games.iter().map(|x| x.pps).sum::<f64>() / self.games.len() as f64,
+        }
+    }
+
+    // The pieces per second of the most recent games, oldest first
+    pub fn recent_pps(&self, count: usize) -> Vec<f64> {
+        let skip = self.games.len().saturating_sub(count);
+
+        self.games.iter().skip(skip).map(|x| x.pps).collect()
+    }
+}
+
+// Games recorded before the tick rate could be configured were all played at the default rate
+fn ticks_per_second() -> u32 {
+    TICKS_PER_SECOND
+}
+
+// Draws the values as a line of block characters between the lowest and the highest value
+pub fn sparkline(values: &[f64]) -> String {
+    let lowest = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let highest = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = highest - lowest;
+
+    values
+        .iter()
+        .map(|value| match range > 0.0 {
+            true => {
+                let index = ((value - lowest) / range * (SPARKS.len() - 1) as f64).round();
+                SPARKS[index as usize]
+            }
+            false => SPARKS[SPARKS.len() / 2],
+        })
+        .collect()
+}
@@ -1,88 +1,336 @@
-use std::sync::mpsc::Sender;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    time::Duration,
+};
 
-use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use tetrs_core::input::Input;
 
+use crate::{
+    bindings::{Bindings, Key, INTERRUPT},
+    game::phase::versus::Player,
+};
+
+const POLL_TIMEOUT: Duration = Duration::from_millis(50);
+
+// What a key asks for, the moves of the piece are handed to the game and the rest is up to the
+// phase that is shown
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub enum Input {
-    Left,
-    Right,
-    Down,
-    Rotate,
-    Drop,
+pub enum Command {
+    Game(Input),
     Quit,
     Restart,
+    Pause,
+    Confirm,
+    Mode,
+    Bot,
+    Statistics,
+    Controls,
     Number(u32),
 }
 
-enum Loop {
-    Continue,
-    Break,
+impl Command {
+    pub const fn game(self) -> Option<Input> {
+        match self {
+            Command::Game(input) => Some(input),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct VersusInput {
+    pub player: Player,
+    pub input: Input,
+}
+
+// Repeats a held movement key after a delay (DAS) at a fixed rate (ARR). This needs the release
+// events of terminals with keyboard enhancement, elsewhere the terminal repeats keys by itself.
+#[derive(Debug, Default)]
+pub struct AutoRepeat {
+    held: Option<Held>,
+}
+
+#[derive(Debug)]
+struct Held {
+    code: KeyCode,
+    input: Input,
+    ticks: u32,
 }
 
 pub struct InputLoop {
-    sender: Sender<Input>,
+    sender: Sender<Event>,
+    stopped: Arc<AtomicBool>,
 }
 
-impl TryFrom<Event> for Input {
-    type Error = ();
+// Controls of a replay that is played back
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Control {
+    Pause,
+    Faster,
+    Slower,
+    Step,
+    Seek(i32),
+    // Plays the replay again from the start
+    Rewind,
+}
 
-    fn try_from(value: Event) -> Result<Self, Self::Error> {
-        match value {
-            Event::Key(e) => {
-                if e.kind == KeyEventKind::Release {
-                    return Err(());
-                }
-                match e.code {
-                    KeyCode::Up => Ok(Input::Rotate),
-                    KeyCode::Right => Ok(Input::Right),
-                    KeyCode::Left => Ok(Input::Left),
-                    KeyCode::Down => Ok(Input::Down),
-                    KeyCode::Char('d') => Ok(Input::Drop),
-                    KeyCode::Char(' ') => Ok(Input::Drop),
-                    KeyCode::Char('q') => Ok(Input::Quit),
-                    KeyCode::Char('r') => Ok(Input::Restart),
-                    KeyCode::Char('c') => match e.modifiers {
-                        KeyModifiers::CONTROL => Ok(Input::Quit),
-                        _ => Err(()),
-                    },
-                    KeyCode::Char(a) => match a {
-                        '0'..='9' => Ok(Input::Number(a.to_digit(10).expect("Should not fail"))),
-                        _ => Err(()),
-                    },
-                    _ => Err(()),
-                }
-            }
-            _ => Err(()),
-        }
+// Keys of the screen that changes the key bindings
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Edit {
+    Previous,
+    Next,
+    Replace,
+    Add,
+    Reset,
+    Back,
+}
+
+// Keys of the menus, independent of the key bindings of the game
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Navigation {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+}
+
+// Keys used to type text like the initials for the high-score table
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Text {
+    Char(char),
+    Erase,
+    Confirm,
+}
+
+pub fn single(event: &Event, bindings: &Bindings) -> Option<Command> {
+    let Event::Key(e) = event else {
+        return None;
+    };
+
+    if e.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    if Key::from_event(e) == Some(INTERRUPT) {
+        return Some(Command::Quit);
+    }
+
+    if let Some(command) = bindings.command(e) {
+        return Some(command);
+    }
+
+    match e.code {
+        KeyCode::Enter => Some(Command::Confirm),
+        KeyCode::Char('m') => Some(Command::Mode),
+        KeyCode::Char('b') => Some(Command::Bot),
+        KeyCode::Char('s') => Some(Command::Statistics),
+        KeyCode::Char('c') => Some(Command::Controls),
+        KeyCode::Char(a) => match a {
+            '0'..='9' => Some(Command::Number(a.to_digit(10).expect("Should not fail"))),
+            _ => None,
+        },
+        _ => None,
     }
 }
 
-impl InputLoop {
-    pub fn new(sender: Sender<Input>) -> Self {
-        Self { sender }
+pub fn text(event: &Event) -> Option<Text> {
+    let Event::Key(e) = event else {
+        return None;
+    };
+
+    if e.kind == KeyEventKind::Release {
+        return None;
     }
 
-    pub fn run(&self) {
-        while let Loop::Continue = self.read_inputs() {}
+    match e.code {
+        KeyCode::Char(c) => Some(Text::Char(c)),
+        KeyCode::Backspace => Some(Text::Erase),
+        KeyCode::Enter => Some(Text::Confirm),
+        _ => None,
+    }
+}
+
+pub fn edit(event: &Event) -> Option<Edit> {
+    let Event::Key(e) = event else {
+        return None;
+    };
+
+    if e.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    match e.code {
+        KeyCode::Up => Some(Edit::Previous),
+        KeyCode::Down => Some(Edit::Next),
+        KeyCode::Enter => Some(Edit::Replace),
+        KeyCode::Char('a') => Some(Edit::Add),
+        KeyCode::Char('r') => Some(Edit::Reset),
+        KeyCode::Esc => Some(Edit::Back),
+        _ => None,
+    }
+}
+
+pub fn navigation(event: &Event) -> Option<Navigation> {
+    let Event::Key(e) = event else {
+        return None;
+    };
+
+    if e.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    match e.code {
+        KeyCode::Up => Some(Navigation::Up),
+        KeyCode::Down => Some(Navigation::Down),
+        KeyCode::Left => Some(Navigation::Left),
+        KeyCode::Right => Some(Navigation::Right),
+        KeyCode::Enter => Some(Navigation::Select),
+        KeyCode::Esc => Some(Navigation::Back),
+        _ => None,
     }
+}
+
+// Any key that is pressed, to be bound to an action
+pub fn key(event: &Event) -> Option<Key> {
+    let Event::Key(e) = event else {
+        return None;
+    };
 
-    fn read_inputs(&self) -> Loop {
-        let input = match crossterm::event::read() {
-            Ok(e) => Input::try_from(e).ok(),
-            Err(_) => None,
-        };
+    if e.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    Key::from_event(e)
+}
+
+pub fn is_interrupt(event: &Event) -> bool {
+    matches!(event, Event::Key(e) if e.kind != KeyEventKind::Release && Key::from_event(e) == Some(INTERRUPT))
+}
+
+// Characters typed without ctrl or alt
+pub fn is_text(event: &Event) -> bool {
+    matches!(event, Event::Key(e) if matches!(e.code, KeyCode::Char(_))
+        && !e.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT))
+}
+
+// The repeated key events of the terminal, replaced by the auto repeat
+pub fn is_repeat(event: &Event) -> bool {
+    matches!(event, Event::Key(e) if e.kind == KeyEventKind::Repeat)
+}
+
+pub fn playback(event: &Event) -> Option<Control> {
+    let Event::Key(e) = event else {
+        return None;
+    };
+
+    if e.kind == KeyEventKind::Release {
+        return None;
+    }
 
-        if let Some(i) = input {
-            self.sender.send(i).ok();
+    match e.code {
+        KeyCode::Char(' ') => Some(Control::Pause),
+        KeyCode::Char('+') | KeyCode::Char('=') => Some(Control::Faster),
+        KeyCode::Char('-') => Some(Control::Slower),
+        KeyCode::Char('.') => Some(Control::Step),
+        KeyCode::Left => Some(Control::Seek(-1)),
+        KeyCode::Right => Some(Control::Seek(1)),
+        KeyCode::Down => Some(Control::Seek(-10)),
+        KeyCode::Up => Some(Control::Seek(10)),
+        _ => None,
+    }
+}
+
+pub fn versus(event: &Event) -> Option<VersusInput> {
+    let Event::Key(e) = event else {
+        return None;
+    };
+
+    if e.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let (player, input) = match e.code {
+        KeyCode::Char('w') => (Player::One, Input::Rotate),
+        KeyCode::Char('a') => (Player::One, Input::Left),
+        KeyCode::Char('s') => (Player::One, Input::Down),
+        KeyCode::Char('d') => (Player::One, Input::Right),
+        KeyCode::Char(' ') => (Player::One, Input::Drop),
+        KeyCode::Up => (Player::Two, Input::Rotate),
+        KeyCode::Left => (Player::Two, Input::Left),
+        KeyCode::Down => (Player::Two, Input::Down),
+        KeyCode::Right => (Player::Two, Input::Right),
+        KeyCode::Enter => (Player::Two, Input::Drop),
+        _ => return None,
+    };
+
+    Some(VersusInput { player, input })
+}
+
+impl AutoRepeat {
+    pub fn update(
+        &mut self,
+        events: &[Event],
+        bindings: &Bindings,
+        delay: u32,
+        rate: u32,
+    ) -> Option<Input> {
+        for event in events {
+            let Event::Key(e) = event else {
+                continue;
+            };
+
+            match e.kind {
+                KeyEventKind::Press => {
+                    if let Some(Command::Game(input @ (Input::Left | Input::Right | Input::Down))) =
+                        bindings.command(e)
+                    {
+                        self.held = Some(Held {
+                            code: e.code,
+                            input,
+                            ticks: 0,
+                        });
+                    }
+                }
+                KeyEventKind::Release => {
+                    if self.held.as_ref().map_or(false, |x| x.code == e.code) {
+                        self.held = None;
+                    }
+                }
+                KeyEventKind::Repeat => (),
+            }
         }
 
-        self.determine_result(&input)
+        let held = self.held.as_mut()?;
+        held.ticks += 1;
+
+        (held.ticks > delay && (held.ticks - delay) % rate == 0).then_some(held.input)
     }
+}
 
-    fn determine_result(&self, input: &Option<Input>) -> Loop {
-        match input {
-            Some(Input::Quit) | Some(Input::Restart) => Loop::Break,
-            _ => Loop::Continue,
+impl InputLoop {
+    pub fn new(sender: Sender<Event>, stopped: Arc<AtomicBool>) -> Self {
+        Self { sender, stopped }
+    }
+
+    // Events are forwarded untranslated because their meaning depends on the phase, e.g. 'd' drops
+    // in a single player game but moves player one to the right in versus. Polling lets the loop
+    // notice that the game is over without waiting for another key
+    pub fn run(&self) {
+        while !self.stopped.load(Ordering::Relaxed) {
+            if !event::poll(POLL_TIMEOUT).unwrap_or(false) {
+                continue;
+            }
+
+            if let Ok(event) = event::read() {
+                self.sender.send(event).ok();
+            }
         }
     }
 }
@@ -1,12 +1,200 @@
-use game::logic::End;
+use std::{fs, path::PathBuf, process::exit};
+
+use cli::{Command, Options, USAGE};
+use config::Config;
+use game::{
+    logic::{Autopilot, End},
+    phase::scores::MODES,
+};
+use net::{Connection, Message, DEFAULT_ADDRESS};
+use scores::HighScores;
+use simulation::simulate;
+use tbp::External;
 use tetrs::Tetrs;
+use tetrs_core::{
+    bot::{Bot, Speed},
+    mode::Mode,
+    script::Script,
+    state::{GameState, TICKS_PER_SECOND},
+};
 
+mod bindings;
+mod cast;
+mod cli;
+mod config;
 mod game;
+mod history;
 mod input;
+mod net;
+mod scores;
+mod simulation;
+mod storage;
+mod tbp;
 mod tetrs;
+mod theme;
 mod ui;
 
 fn main() {
-    let game = Tetrs::new();
-    while game.run() != End::Quit {}
+    let (command, options) = cli::parse(std::env::args().skip(1))
+        .unwrap_or_else(|e| fail(format!("{e}\nRun tetrs --help for the usage")));
+
+    match command {
+        Command::Help => return println!("tetrs {}\n{USAGE}", env!("CARGO_PKG_VERSION")),
+        Command::Version => return println!("tetrs {}", env!("CARGO_PKG_VERSION")),
+        _ => {}
+    }
+
+    // Mistakes in the config are reported before the terminal is taken over
+    let config = Config::load(options.config.clone())
+        .and_then(|x| x.apply(&options))
+        .unwrap_or_else(|e| fail(e));
+
+    match command {
+        Command::Menu => menu(config, options.seed),
+        Command::Play => play(config, options.seed),
+        Command::Serve(address) => serve(config, address),
+        Command::Join(address) => join(config, &address),
+        Command::Tbp(program, args) => external(config, &program, &args),
+        Command::Replay(path) => replay(config, &path),
+        Command::Export(path, output) => export(&config, &path, output),
+        Command::Scores => scores(&config, &options),
+        Command::Simulate(source) => headless(&config, &source, &options),
+        Command::Help | Command::Version => {}
+    }
+}
+
+fn menu(config: Config, seed: Option<u64>) {
+    let mut game = Tetrs::new(config);
+    while game.run(seed) != End::Quit {}
+}
+
+// Restarting starts the same kind of game again instead of going to the menu
+fn play(config: Config, seed: Option<u64>) {
+    let mut game = Tetrs::new(config);
+    while game.run_play(seed) != End::Quit {}
+}
+
+fn serve(config: Config, address: Option<String>) {
+    let address = address.as_deref().unwrap_or(DEFAULT_ADDRESS);
+    let level = config.level;
+
+    println!("Waiting for an opponent on {address}");
+
+    let mut connection = Connection::serve(address).unwrap_or_else(|e| fail(e));
+    connection
+        .send(&Message::Start(level))
+        .unwrap_or_else(|e| fail(e));
+
+    Tetrs::new(config).run_online(connection, level);
+}
+
+fn join(config: Config, address: &str) {
+    let connection = Connection::join(address).unwrap_or_else(|e| fail(e));
+
+    match connection.wait() {
+        Ok(Message::Start(level)) => {
+            Tetrs::new(config).run_online(connection, level);
+        }
+        _ => fail("The opponent did not start a game"),
+    }
+}
+
+// Restarting plays the next game against a new instance of the bot
+fn external(config: Config, program: &str, args: &[String]) {
+    let mut external = External::spawn(program, args).unwrap_or_else(|e| fail(e));
+
+    let level = config.level;
+    let mut game = Tetrs::new(config);
+
+    while game.run_external(external, level) == End::Restart {
+        external = match External::spawn(program, args) {
+            Ok(external) => external,
+            Err(e) => {
+                drop(game);
+                fail(e)
+            }
+        };
+    }
+}
+
+fn replay(config: Config, path: &str) {
+    let replay = storage::load_replay(path).unwrap_or_else(|e| fail(format!("{path}: {e}")));
+
+    Tetrs::new(config).run_replay(replay);
+}
+
+fn export(config: &Config, path: &str, output: Option<String>) {
+    let replay = storage::load_replay(path).unwrap_or_else(|e| fail(format!("{path}: {e}")));
+    let output = output.map_or_else(|| PathBuf::from(path).with_extension("cast"), PathBuf::from);
+
+    cast::export(&replay, config, &output)
+        .unwrap_or_else(|e| fail(format!("{}: {e}", output.display())));
+
+    println!("Exported to {}", output.display());
+}
+
+fn scores(config: &Config, options: &Options) {
+    let modes = options.mode.map_or(MODES.to_vec(), |x| vec![x]);
+
+    for mode in modes {
+        let scores = HighScores::load();
+        let mut top = scores.top(mode, config.tick_rate).peekable();
+
+        match config.tick_rate {
+            TICKS_PER_SECOND => println!("{}", mode.name()),
+            rate => println!("{} at {rate} ticks per second", mode.name()),
+        }
+
+        if top.peek().is_none() {
+            println!("  No games yet");
+        }
+
+        for (index, score) in top.enumerate() {
+            println!(
+                "{:>3}. {:<16} {:>7} {:>4} lines  level {:>2}  {:>6}",
+                index + 1,
+                score.name,
+                score.score,
+                score.lines,
+                score.level,
+                ui::duration(score.seconds())
+            );
+        }
+    }
+}
+
+fn headless(config: &Config, source: &str, options: &Options) {
+    let autopilot = match source {
+        "bot" => Autopilot::Bot(Bot::new(Speed::Fast)),
+        path => {
+            let text = fs::read_to_string(path).unwrap_or_else(|e| fail(format!("{path}: {e}")));
+            Autopilot::Script(Script::parse(&text).unwrap_or_else(|e| fail(format!("{path}: {e}"))))
+        }
+    };
+
+    // The mode of the menu in the config does not apply here
+    let mode = match options.mode {
+        None => Mode::Marathon,
+        // Zen games are never lost, so they would run forever
+        Some(Mode::Zen) if options.pieces.is_none() => fail("Simulating zen games needs --pieces"),
+        Some(mode @ (Mode::Marathon | Mode::Zen)) => mode,
+        Some(mode) => fail(format!("Cannot simulate {} games", mode.name())),
+    };
+
+    let state = match options.seed {
+        Some(seed) => GameState::seeded(config.level, mode, seed),
+        None => GameState::new(config.level, mode),
+    };
+
+    let state = simulate(state, autopilot, options.pieces);
+
+    println!("Score: {}", state.level.score);
+    println!("Lines: {}", state.level.cleared_lines);
+    println!("Level: {}", state.level.current);
+    println!("Pieces: {}", state.pieces);
+}
+
+fn fail<E: std::fmt::Display>(error: E) -> ! {
+    eprintln!("{error}");
+    exit(1)
 }
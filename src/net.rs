@@ -0,0 +1,265 @@
+use std::{
+    io::{self, BufReader, BufWriter, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use tetrs_core::{
+    state::{GameState, Square, FIELD_HEIGHT, FIELD_WIDTH},
+    tetromino::Kind,
+};
+
+pub const DEFAULT_ADDRESS: &str = "0.0.0.0:7474";
+
+const START: u8 = 0;
+const BOARD: u8 = 1;
+const GARBAGE: u8 = 2;
+const LOST: u8 = 3;
+
+// Every frame on the wire is a one byte tag followed by a big endian u16 payload length and the payload
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Start(u32),
+    Board(Snapshot),
+    Garbage(u32),
+    Lost,
+}
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Snapshot {
+    pub cells: Vec<u8>,
+    pub level: u32,
+    pub lines: u32,
+    pub score: u32,
+}
+
+pub struct Disconnected;
+
+pub struct Connection {
+    writer: BufWriter<TcpStream>,
+    messages: Receiver<Message>,
+}
+
+impl Connection {
+    pub fn serve<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let (stream, _) = listener.accept()?;
+
+        Self::new(stream)
+    }
+
+    pub fn join<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Self::new(TcpStream::connect(address)?)
+    }
+
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (sender, messages) = mpsc::channel();
+
+        thread::spawn(move || {
+            while let Ok(message) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            writer: BufWriter::new(stream),
+            messages,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let (tag, payload) = encode(message);
+
+        self.writer.write_all(&[tag])?;
+        self.writer
+            .write_all(&(payload.len() as u16).to_be_bytes())?;
+        self.writer.write_all(&payload)?;
+        self.writer.flush()
+    }
+
+    pub fn receive(&self) -> Result<Vec<Message>, Disconnected> {
+        let mut messages = Vec::new();
+
+        loop {
+            match self.messages.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => return Ok(messages),
+                Err(TryRecvError::Disconnected) => return Err(Disconnected),
+            }
+        }
+    }
+
+    pub fn wait(&self) -> Result<Message, Disconnected> {
+        self.messages.recv().map_err(|_| Disconnected)
+    }
+}
+
+// The reading thread holds a clone of the stream, which would otherwise keep the connection open
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.writer.get_ref().shutdown(Shutdown::Both).ok();
+    }
+}
+
+fn read_message<R: Read>(reader: &mut R) -> io::Result<Message> {
+    let mut header = [0u8; 3];
+    reader.read_exact(&mut header)?;
+
+    let mut payload = vec![0u8; u16::from_be_bytes([header[1], header[2]]) as usize];
+    reader.read_exact(&mut payload)?;
+
+    decode(header[0], &payload)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed frame"))
+}
+
+fn encode(message: &Message) -> (u8, Vec<u8>) {
+    match message {
+        Message::Start(level) => (START, level.to_be_bytes().to_vec()),
+        Message::Board(snapshot) => {
+            let mut payload = snapshot.cells.clone();
+            for value in [snapshot.level, snapshot.lines, snapshot.score] {
+                payload.extend_from_slice(&value.to_be_bytes());
+            }
+
+            (BOARD, payload)
+        }
+        Message::Garbage(lines) => (GARBAGE, lines.to_be_bytes().to_vec()),
+        Message::Lost => (LOST, Vec::new()),
+    }
+}
+
+fn decode(tag: u8, payload: &[u8]) -> Option<Message> {
+    let number = |index: usize| -> Option<u32> {
+        let bytes = payload.get(index..index + 4)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?))
+    };
+
+    match tag {
+        START => Some(Message::Start(number(0)?)),
+        BOARD => {
+            let cells = FIELD_WIDTH * FIELD_HEIGHT;
+
+            Some(Message::Board(Snapshot {
+                cells: payload.get(..cells)?.to_vec(),
+                level: number(cells)?,
+                lines: number(cells + 4)?,
+                score: number(cells + 8)?,
+            }))
+        }
+        GARBAGE => Some(Message::Garbage(number(0)?)),
+        LOST => Some(Message::Lost),
+        _ => None,
+    }
+}
+
+impl From<&GameState> for Snapshot {
+    fn from(state: &GameState) -> Self {
+        let mut cells: Vec<u8> = state
+            .field
+            .iter()
+            .flat_map(|line| line.iter().map(code))
+            .collect();
+
+        let current = code(&Square::Occupied(state.current.kind));
+        for block in state.current.offset_blocks().iter() {
+            if let Some(cell) =
+                cells.get_mut(block.vec.y as usize * FIELD_WIDTH + block.vec.x as usize)
+            {
+                *cell = current;
+            }
+        }
+
+        Self {
+            cells,
+            level: state.level.current,
+            lines: state.level.cleared_lines,
+            score: state.level.score,
+        }
+    }
+}
+
+const KINDS: [Kind; 7] = [
+    Kind::I,
+    Kind::O,
+    Kind::T,
+    Kind::S,
+    Kind::Z,
+    Kind::J,
+    Kind::L,
+];
+const GARBAGE_CODE: u8 = KINDS.len() as u8 + 1;
+
+fn code(square: &Square) -> u8 {
+    match square {
+        Square::Empty => 0,
+        Square::Occupied(kind) => {
+            KINDS.iter().position(|x| x == kind).unwrap_or_default() as u8 + 1
+        }
+        Square::Garbage => GARBAGE_CODE,
+    }
+}
+
+pub fn square(code: u8) -> Square {
+    match code {
+        0 => Square::Empty,
+        _ => KINDS
+            .get(code as usize - 1)
+            .map_or(Square::Garbage, |&kind| Square::Occupied(kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tetrs_core::mode::Mode;
+
+    use super::*;
+
+    #[test]
+    fn connection_over_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let joining = thread::spawn(move || Connection::join(address).unwrap());
+        let mut server = Connection::new(listener.accept().unwrap().0).unwrap();
+        let mut client = joining.join().unwrap();
+
+        let snapshot = Snapshot::from(&GameState::seeded(0, Mode::Versus, 7));
+        let messages = [
+            Message::Start(5),
+            Message::Board(snapshot),
+            Message::Garbage(3),
+            Message::Lost,
+        ];
+
+        for message in messages.iter() {
+            server.send(message).unwrap();
+        }
+
+        for message in messages.iter() {
+            assert_eq!(client.wait().ok().as_ref(), Some(message));
+        }
+
+        client.send(&Message::Garbage(1)).unwrap();
+        assert_eq!(server.wait().ok(), Some(Message::Garbage(1)));
+
+        // The other side notices when the connection is gone
+        drop(client);
+        assert!(server.wait().is_err());
+        assert!(server.receive().is_err());
+
+        // Writing fails as soon as the peer has refused the first message after it left
+        let failed = (0..100).any(|_| {
+            thread::sleep(Duration::from_millis(10));
+            server.send(&Message::Lost).is_err()
+        });
+        assert!(failed);
+    }
+}
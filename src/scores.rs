@@ -0,0 +1,119 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use tetrs_core::{
+    mode::Mode,
+    state::{GameState, TICKS_PER_SECOND},
+};
+
+use crate::storage;
+
+const FILE: &str = "scores.json";
+const TABLE_SIZE: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    pub mode: Mode,
+    pub score: u32,
+    pub lines: u32,
+    pub level: u32,
+    pub time: u64,
+    #[serde(default = "ticks_per_second")]
+    pub tick_rate: u32,
+    pub date: u64,
+    pub name: String,
+    pub seed: u64,
+}
+
+impl Score {
+    pub fn new(state: &GameState, name: String) -> Self {
+        Self {
+            mode: state.mode,
+            score: state.level.score,
+            lines: state.level.cleared_lines,
+            level: state.level.current,
+            time: state.time,
+            tick_rate: state.tick_rate,
+            date: storage::timestamp(),
+            name,
+            seed: state.seed,
+        }
+    }
+
+    pub fn seconds(&self) -> u64 {
+        self.time / self.tick_rate as u64
+    }
+
+    // Games played at another tick rate are not comparable and keep a table of their own
+    fn table(&self) -> (Mode, u32) {
+        (self.mode, self.tick_rate)
+    }
+}
+
+// The best games of every mode, best first
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    scores: Vec<Score>,
+}
+
+impl HighScores {
+    // A missing or broken file starts a new table
+    pub fn load() -> Self {
+        storage::load(FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(FILE, self).ok();
+    }
+
+    pub fn top(&self, mode: Mode, tick_rate: u32) -> impl Iterator<Item = &Score> {
+        self.scores
+            .iter()
+            .filter(move |x| x.table() == (mode, tick_rate))
+    }
+
+    fn index(&self, score: &Score) -> usize {
+        self.scores
+            .iter()
+            .position(|x| (x.score, x.lines) < (score.score, score.lines))
+            .unwrap_or(self.scores.len())
+    }
+
+    // The rank the score would get in the table of its mode, if it is good enough
+    pub fn rank(&self, score: &Score) -> Option<usize> {
+        let rank = self.scores[..self.index(score)]
+            .iter()
+            .filter(|x| x.table() == score.table())
+            .count();
+
+        (rank < TABLE_SIZE).then_some(rank)
+    }
+
+    pub fn add(&mut self, score: Score) -> Option<usize> {
+        let rank = self.rank(&score)?;
+        let index = self.index(&score);
+
+        let table = score.table();
+        self.scores.insert(index, score);
+
+        let mut count = 0;
+        self.scores.retain(|x| {
+            count += usize::from(x.table() == table);
+            x.table() != table || count <= TABLE_SIZE
+        });
+
+        Some(rank)
+    }
+}
+
+// Used when no initials are entered
+pub fn default_name() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("player"))
+}
+
+// Scores saved before the tick rate could be configured were all played at the default rate
+fn ticks_per_second() -> u32 {
+    TICKS_PER_SECOND
+}
@@ -0,0 +1,29 @@
+use tetrs_core::state::GameState;
+
+use crate::game::{
+    cycle::{GameLoop, VirtualClock},
+    logic::{Autopilot, Logic, TickResult},
+    phase::Phase,
+};
+
+// Runs a game without a terminal as fast as possible until it is lost, quit or the given number
+// of pieces has been played. A game that is quit before its first tick ends as it started.
+pub fn simulate(state: GameState, autopilot: Autopilot, pieces: Option<u32>) -> GameState {
+    let mut last = state.clone();
+    let logic = Logic::simulated(state, autopilot);
+    let mut game = GameLoop::with_clock(logic, None, VirtualClock::default());
+
+    loop {
+        let state = match game.step() {
+            TickResult::Phase(Phase::Running(running)) => running.state,
+            TickResult::Phase(Phase::Finished(finished)) => return finished.state,
+            _ => return last,
+        };
+
+        if pieces.map_or(false, |pieces| state.pieces >= pieces) {
+            return state;
+        }
+
+        last = state;
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+use serde::{de::DeserializeOwned, Serialize};
+use tetrs_core::{replay::Replay, state::GameState};
+
+const SAVED_GAME: &str = "saved.json";
+
+// A single player game that was quit while running, offered to be resumed on the next launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub state: GameState,
+    pub recording: Option<Replay>,
+}
+
+// Everything tetrs writes lives in the data directory of the platform, e.g. ~/.local/share/tetrs
+fn directory(name: &str) -> io::Result<PathBuf> {
+    let directory = dirs::data_dir()
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "No data directory found"))?
+        .join("tetrs")
+        .join(name);
+
+    fs::create_dir_all(&directory)?;
+
+    Ok(directory)
+}
+
+pub fn path(name: &str) -> io::Result<PathBuf> {
+    Ok(directory("")?.join(name))
+}
+
+pub fn load<T: DeserializeOwned>(name: &str) -> io::Result<T> {
+    Ok(serde_json::from_str(&fs::read_to_string(path(name)?)?)?)
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    fs::write(path(name)?, serde_json::to_string(value)?)
+}
+
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+// Replays of games that end in the same second are numbered, a replay is never overwritten
+pub fn save_replay(replay: &Replay) -> io::Result<PathBuf> {
+    let directory = directory("replays")?;
+    let name = format!("{}-{}", timestamp(), replay.mode.name());
+    let contents = serde_json::to_string(replay)?;
+
+    let mut count = 1;
+    loop {
+        let path = match count {
+            1 => directory.join(format!("{name}.json")),
+            _ => directory.join(format!("{name}-{count}.json")),
+        };
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => count += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+pub fn load_replay(path: &str) -> io::Result<Replay> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn save_game(saved: &SavedGame) -> io::Result<()> {
+    save(SAVED_GAME, saved)
+}
+
+// A saved game that cannot be read is treated as if there was none
+pub fn load_game() -> Option<SavedGame> {
+    load(SAVED_GAME).ok()
+}
+
+pub fn delete_game() -> io::Result<()> {
+    fs::remove_file(path(SAVED_GAME)?)
+}
@@ -0,0 +1,332 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tetrs_core::{
+    input::Input,
+    placement::Placement,
+    state::{Field, GameState, Square, FIELD_HEIGHT},
+    tetromino::Kind,
+};
+
+// The board sent to bots is 40 rows high, everything above our field is empty
+const BOARD_HEIGHT: usize = 40;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Spin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+// The position of a piece in the coordinate system of the protocol, x and y are the center of the
+// piece as defined by SRS and y grows upwards starting at the bottom row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(rename = "type")]
+    pub kind: Kind,
+    pub orientation: Orientation,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    pub location: Location,
+    #[serde(default)]
+    pub spin: Spin,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Rules {},
+    Start {
+        hold: Option<Kind>,
+        queue: Vec<Kind>,
+        combo: u32,
+        back_to_back: bool,
+        board: Vec<Vec<Option<char>>>,
+    },
+    Suggest,
+    Play {
+        #[serde(rename = "move")]
+        placement: Move,
+    },
+    NewPiece {
+        piece: Kind,
+    },
+    Stop,
+    Quit,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Reply {
+    Info {
+        name: String,
+    },
+    Ready,
+    Error {
+        reason: String,
+    },
+    Suggestion {
+        moves: Vec<Move>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+// An external bot that speaks the Tetris Bot Protocol on its stdin and stdout
+pub struct External {
+    pub name: String,
+    // Suggestions that no inputs lead to, the piece is left to fall where gravity takes it
+    pub unreachable: u32,
+    child: Child,
+    stdin: ChildStdin,
+    replies: Receiver<Reply>,
+    piece: Option<u32>,
+    expected: Option<Field>,
+    started: bool,
+    waiting: bool,
+    // The squares of the suggestion that is played
+    target: Option<Vec<(i32, i32)>>,
+}
+
+impl External {
+    pub fn spawn(program: &str, args: &[String]) -> Result<Self, String> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Could not start {program}: {e}"))?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, replies) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Ok(reply) = serde_json::from_str(&line) {
+                    if sender.send(reply).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let mut external = Self {
+            name: String::from(program),
+            unreachable: 0,
+            child,
+            stdin,
+            replies,
+            piece: None,
+            expected: None,
+            started: false,
+            waiting: false,
+            target: None,
+        };
+
+        external.handshake()?;
+
+        Ok(external)
+    }
+
+    fn handshake(&mut self) -> Result<(), String> {
+        match self.wait()? {
+            Reply::Info { name } => self.name = name,
+            _ => return Err(String::from("The bot did not introduce itself")),
+        }
+
+        self.send(&Request::Rules {});
+
+        match self.wait()? {
+            Reply::Ready => Ok(()),
+            Reply::Error { reason } => Err(format!("The bot refused to play: {reason}")),
+            _ => Err(String::from("The bot did not get ready")),
+        }
+    }
+
+    fn wait(&self) -> Result<Reply, String> {
+        self.replies
+            .recv_timeout(HANDSHAKE_TIMEOUT)
+            .map_err(|e| match e {
+                RecvTimeoutError::Timeout => String::from("The bot did not answer in time"),
+                RecvTimeoutError::Disconnected => String::from("The bot exited"),
+            })
+    }
+
+    fn send(&mut self, request: &Request) {
+        let line = serde_json::to_string(request).expect("requests are always serializable");
+
+        writeln!(self.stdin, "{line}").ok();
+        self.stdin.flush().ok();
+    }
+
+    // Asks for a suggestion whenever a new piece spawns and moves the piece towards it one input
+    // per call
+    pub fn next_input(&mut self, state: &GameState) -> Option<Input> {
+        if self.piece != Some(state.pieces) {
+            self.piece = Some(state.pieces);
+            self.request(state);
+        }
+
+        if self.waiting {
+            self.receive(state);
+        }
+
+        self.target
+            .as_deref()
+            .and_then(|x| state.placement_at(x))
+            .and_then(|x| x.path.first().copied())
+    }
+
+    fn request(&mut self, state: &GameState) {
+        self.target = None;
+
+        match &self.expected {
+            // The bot already knows the board, so it only needs to learn about the new preview
+            Some(field) if field == &state.field => self.send(&Request::NewPiece {
+                piece: state.next.kind,
+            }),
+            // Garbage arrived or the last suggestion could not be played, start over
+            _ => {
+                if self.started {
+                    self.send(&Request::Stop);
+                }
+
+                self.send(&start(state));
+                self.started = true;
+            }
+        }
+
+        self.send(&Request::Suggest);
+        self.waiting = true;
+    }
+
+    fn receive(&mut self, state: &GameState) {
+        while let Ok(reply) = self.replies.try_recv() {
+            let Reply::Suggestion { moves } = reply else {
+                continue;
+            };
+
+            let reachable = moves
+                .into_iter()
+                .find_map(|x| find(state, &x.location).map(|placement| (x, placement)));
+
+            match reachable {
+                Some((suggested, placement)) => {
+                    self.expected = Some(state.place(&placement.tetromino).0);
+                    self.target = Some(placement.squares());
+                    self.send(&Request::Play {
+                        placement: suggested,
+                    });
+                }
+                None => {
+                    self.expected = None;
+                    self.unreachable += 1;
+                }
+            }
+
+            self.waiting = false;
+            return;
+        }
+    }
+}
+
+impl Drop for External {
+    fn drop(&mut self) {
+        self.send(&Request::Quit);
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+fn start(state: &GameState) -> Request {
+    let mut board: Vec<Vec<Option<char>>> = state
+        .field
+        .iter()
+        .rev()
+        .map(|line| line.iter().map(cell).collect())
+        .collect();
+    board.resize(BOARD_HEIGHT, vec![None; board[0].len()]);
+
+    Request::Start {
+        hold: None,
+        queue: vec![state.current.kind, state.next.kind],
+        combo: state.attack.combo.map_or(0, |x| x + 1),
+        back_to_back: state.attack.back_to_back,
+        board,
+    }
+}
+
+fn cell(square: &Square) -> Option<char> {
+    match square {
+        Square::Empty => None,
+        Square::Occupied(kind) => Some(match kind {
+            Kind::I => 'I',
+            Kind::O => 'O',
+            Kind::T => 'T',
+            Kind::S => 'S',
+            Kind::Z => 'Z',
+            Kind::J => 'J',
+            Kind::L => 'L',
+        }),
+        Square::Garbage => Some('G'),
+    }
+}
+
+// Finds the reachable placement that covers exactly the squares of the suggested location
+fn find(state: &GameState, location: &Location) -> Option<Placement> {
+    if location.kind != state.current.kind {
+        return None;
+    }
+
+    let mut target = squares(location);
+    target.sort();
+
+    state.placement_at(&target)
+}
+
+fn squares(location: &Location) -> Vec<(i32, i32)> {
+    let north: [(i32, i32); 4] = match location.kind {
+        Kind::I => [(-1, 0), (0, 0), (1, 0), (2, 0)],
+        Kind::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        Kind::T => [(-1, 0), (0, 0), (1, 0), (0, 1)],
+        Kind::S => [(-1, 0), (0, 0), (0, 1), (1, 1)],
+        Kind::Z => [(-1, 1), (0, 1), (0, 0), (1, 0)],
+        Kind::J => [(-1, 1), (-1, 0), (0, 0), (1, 0)],
+        Kind::L => [(-1, 0), (0, 0), (1, 0), (1, 1)],
+    };
+
+    north
+        .iter()
+        .map(|&(x, y)| match location.orientation {
+            Orientation::North => (x, y),
+            Orientation::East => (y, -x),
+            Orientation::South => (-x, -y),
+            Orientation::West => (-y, x),
+        })
+        .map(|(x, y)| (location.x + x, FIELD_HEIGHT as i32 - 1 - (location.y + y)))
+        .collect()
+}
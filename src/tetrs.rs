@@ -1,51 +1,133 @@
 use std::{
     io::stdout,
-    marker::PhantomData,
-    sync::mpsc::{self, Receiver, Sender},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
     thread,
 };
 
 use crossterm::{
-    cursor, execute,
+    cursor,
+    event::{
+        DisableFocusChange, EnableFocusChange, Event, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
     terminal::{
-        disable_raw_mode, enable_raw_mode, Clear, EnterAlternateScreen, LeaveAlternateScreen,
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, Clear,
+        EnterAlternateScreen, LeaveAlternateScreen,
     },
 };
 
+use tetrs_core::replay::Replay;
+
 use crate::{
+    config::Config,
     game::{
         cycle::GameLoop,
         logic::{End, Logic},
     },
-    input::{Input, InputLoop},
+    input::InputLoop,
+    net::Connection,
+    tbp::External,
     ui::Ui,
 };
 
 pub struct Tetrs {
-    phantom: PhantomData<()>,
+    config: Config,
+    enhanced: bool,
 }
 
 impl Tetrs {
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         enable_raw_mode().unwrap();
-        execute!(stdout(), EnterAlternateScreen, cursor::Hide).unwrap();
+        execute!(
+            stdout(),
+            EnterAlternateScreen,
+            cursor::Hide,
+            EnableFocusChange
+        )
+        .unwrap();
 
-        Self {
-            phantom: PhantomData,
+        // Release events are needed to repeat held keys with the delay and rate of the config
+        let enhanced = supports_keyboard_enhancement().unwrap_or(false);
+
+        if enhanced {
+            execute!(
+                stdout(),
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )
+            .unwrap();
         }
+
+        Self { config, enhanced }
+    }
+
+    pub fn run(&mut self, seed: Option<u64>) -> End {
+        let tick_rate = self.config.tick_rate;
+        self.run_with(tick_rate, |receiver, config| {
+            Logic::new(receiver, config, seed)
+        })
+    }
+
+    pub fn run_play(&mut self, seed: Option<u64>) -> End {
+        let tick_rate = self.config.tick_rate;
+        self.run_with(tick_rate, |receiver, config| {
+            Logic::play(receiver, config, seed)
+        })
+    }
+
+    pub fn run_online(&mut self, connection: Connection, level: u32) -> End {
+        let tick_rate = self.config.tick_rate;
+        self.run_with(tick_rate, |receiver, config| {
+            Logic::online(receiver, config, connection, level)
+        })
     }
 
-    pub fn run(&self) -> End {
+    pub fn run_external(&mut self, external: External, level: u32) -> End {
+        let tick_rate = self.config.tick_rate;
+        self.run_with(tick_rate, |receiver, config| {
+            Logic::against(receiver, config, external, level)
+        })
+    }
+
+    pub fn run_replay(&mut self, replay: Replay) -> End {
+        // A replay is shown at the speed it was played at
+        self.run_with(replay.tick_rate, |receiver, config| {
+            Logic::replay(receiver, config, replay)
+        })
+    }
+
+    // Changes to the config made in the game are kept for the following runs
+    fn run_with<F: FnOnce(Receiver<Event>, Config) -> Logic>(
+        &mut self,
+        tick_rate: u32,
+        logic: F,
+    ) -> End {
         execute!(stdout(), Clear(crossterm::terminal::ClearType::All)).unwrap();
 
-        let (sender, receiver): (Sender<Input>, Receiver<Input>) = mpsc::channel();
+        let (sender, receiver): (Sender<Event>, Receiver<Event>) = mpsc::channel();
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let input_stopped = stopped.clone();
 
         let input_thread = thread::spawn(|| {
-            InputLoop::new(sender).run();
+            InputLoop::new(sender, input_stopped).run();
         });
 
-        let end = GameLoop::new(Logic::new(receiver), Ui::default()).run();
+        let mut logic = logic(receiver, self.config.clone());
 
+        if self.enhanced {
+            logic.repeat_keys();
+        }
+
+        let mut game = GameLoop::new(logic, Ui::default(), tick_rate);
+        let end = game.run();
+        self.config = game.config().clone();
+
+        stopped.store(true, Ordering::Relaxed);
         input_thread.join().unwrap();
 
         end
@@ -54,7 +136,17 @@ impl Tetrs {
 
 impl Drop for Tetrs {
     fn drop(&mut self) {
+        if self.enhanced {
+            execute!(stdout(), PopKeyboardEnhancementFlags).unwrap();
+        }
+
         disable_raw_mode().unwrap();
-        execute!(stdout(), LeaveAlternateScreen, cursor::Show).unwrap();
+        execute!(
+            stdout(),
+            DisableFocusChange,
+            LeaveAlternateScreen,
+            cursor::Show
+        )
+        .unwrap();
     }
 }
@@ -0,0 +1,249 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tetrs_core::{state::Square, tetromino::Kind};
+use tui::style::Color;
+
+pub const GUIDELINE: &str = "guideline";
+pub const BUILT_IN: [&str; 5] = [
+    GUIDELINE,
+    "nes",
+    "monochrome",
+    "high-contrast",
+    "colorblind",
+];
+
+// Orange of the 256 color palette, which more terminals support than RGB colors
+const ORANGE: Color = Color::Indexed(208);
+
+// The colors of the pieces and of the garbage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colors {
+    pub i: Color,
+    pub o: Color,
+    pub t: Color,
+    pub s: Color,
+    pub z: Color,
+    pub j: Color,
+    pub l: Color,
+    pub garbage: Color,
+}
+
+// A theme of the config, the colors that are left out come from its base
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    #[serde(default = "guideline")]
+    pub base: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub i: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub o: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub t: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub s: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub z: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub j: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub l: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "color_name", deserialize_with = "color")]
+    pub garbage: Option<Color>,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Self {
+            i: Color::Cyan,
+            o: Color::Yellow,
+            t: Color::Magenta,
+            s: Color::Green,
+            z: Color::Red,
+            j: Color::Blue,
+            l: ORANGE,
+            garbage: Color::Gray,
+        }
+    }
+}
+
+impl Colors {
+    pub fn built_in(name: &str) -> Option<Self> {
+        let colors = match name {
+            GUIDELINE => Self::default(),
+            // The pieces of level 0 of the NES version
+            "nes" => Self {
+                i: Color::White,
+                o: Color::White,
+                t: Color::White,
+                s: Color::Rgb(0x00, 0x58, 0xf8),
+                z: Color::Rgb(0x3c, 0xbc, 0xfc),
+                j: Color::Rgb(0x00, 0x58, 0xf8),
+                l: Color::Rgb(0x3c, 0xbc, 0xfc),
+                garbage: Color::DarkGray,
+            },
+            "monochrome" => Self {
+                i: Color::White,
+                o: Color::White,
+                t: Color::White,
+                s: Color::White,
+                z: Color::White,
+                j: Color::White,
+                l: Color::White,
+                garbage: Color::DarkGray,
+            },
+            "high-contrast" => Self {
+                i: Color::LightCyan,
+                o: Color::LightYellow,
+                t: Color::LightMagenta,
+                s: Color::LightGreen,
+                z: Color::LightRed,
+                j: Color::LightBlue,
+                l: Color::Indexed(214),
+                garbage: Color::White,
+            },
+            // The palette of Okabe and Ito, which stays apart for every kind of color blindness
+            "colorblind" => Self {
+                i: Color::Rgb(0x56, 0xb4, 0xe9),
+                o: Color::Rgb(0xf0, 0xe4, 0x42),
+                t: Color::Rgb(0xcc, 0x79, 0xa7),
+                s: Color::Rgb(0x00, 0x9e, 0x73),
+                z: Color::Rgb(0xd5, 0x5e, 0x00),
+                j: Color::Rgb(0x00, 0x72, 0xb2),
+                l: Color::Rgb(0xe6, 0x9f, 0x00),
+                garbage: Color::Gray,
+            },
+            _ => return None,
+        };
+
+        Some(colors)
+    }
+
+    pub const fn kind(&self, kind: Kind) -> Color {
+        match kind {
+            Kind::I => self.i,
+            Kind::O => self.o,
+            Kind::T => self.t,
+            Kind::S => self.s,
+            Kind::Z => self.z,
+            Kind::J => self.j,
+            Kind::L => self.l,
+        }
+    }
+
+    pub const fn square(&self, square: &Square) -> Option<Color> {
+        match square {
+            Square::Empty => None,
+            Square::Occupied(kind) => Some(self.kind(*kind)),
+            Square::Garbage => Some(self.garbage),
+        }
+    }
+}
+
+impl Theme {
+    // Only built-in themes can be the base of another theme
+    pub fn colors(&self) -> Option<Colors> {
+        let base = Colors::built_in(&self.base)?;
+
+        Some(Colors {
+            i: self.i.unwrap_or(base.i),
+            o: self.o.unwrap_or(base.o),
+            t: self.t.unwrap_or(base.t),
+            s: self.s.unwrap_or(base.s),
+            z: self.z.unwrap_or(base.z),
+            j: self.j.unwrap_or(base.j),
+            l: self.l.unwrap_or(base.l),
+            garbage: self.garbage.unwrap_or(base.garbage),
+        })
+    }
+}
+
+fn guideline() -> String {
+    String::from(GUIDELINE)
+}
+
+// A name, a number of the 256 color palette or #rrggbb
+fn parse_color(name: &str) -> Result<Color, String> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let value = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)
+            .ok_or_else(|| format!("Invalid color {name}"))?;
+
+        return Ok(Color::Rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ));
+    }
+
+    if let Ok(index) = name.parse() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let color = match name.to_lowercase().replace(['_', '-', ' '], "").as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        "orange" => ORANGE,
+        "reset" => Color::Reset,
+        _ => return Err(format!("Unknown color {name}")),
+    };
+
+    Ok(color)
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    parse_color(&String::deserialize(deserializer)?)
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
+fn color_name<S: Serializer>(color: &Option<Color>, serializer: S) -> Result<S::Ok, S::Error> {
+    let name = match color {
+        Some(Color::Black) => "black",
+        Some(Color::Red) => "red",
+        Some(Color::Green) => "green",
+        Some(Color::Yellow) => "yellow",
+        Some(Color::Blue) => "blue",
+        Some(Color::Magenta) => "magenta",
+        Some(Color::Cyan) => "cyan",
+        Some(Color::Gray) => "gray",
+        Some(Color::DarkGray) => "dark-gray",
+        Some(Color::LightRed) => "light-red",
+        Some(Color::LightGreen) => "light-green",
+        Some(Color::LightYellow) => "light-yellow",
+        Some(Color::LightBlue) => "light-blue",
+        Some(Color::LightMagenta) => "light-magenta",
+        Some(Color::LightCyan) => "light-cyan",
+        Some(Color::White) => "white",
+        Some(ORANGE) => "orange",
+        Some(Color::Indexed(index)) => return serializer.collect_str(index),
+        Some(Color::Rgb(r, g, b)) => {
+            return serializer.collect_str(&format!("#{r:02x}{g:02x}{b:02x}"))
+        }
+        _ => "reset",
+    };
+
+    serializer.serialize_str(name)
+}
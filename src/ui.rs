@@ -6,19 +6,37 @@ use std::{
 };
 
 use tui::{
-    backend::CrosstermBackend,
+    backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::Style,
+    style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, Paragraph, Row, Table, Wrap},
-    Terminal,
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table},
+    Frame, Terminal,
 };
 
-use crate::game::{
-    level::Level,
-    phase::Phase,
-    state::{Field, GameState, Square, FIELD_HEIGHT, FIELD_WIDTH},
-    tetromino::Tetromino,
+use tetrs_core::{
+    state::{Field, GameState, FIELD_HEIGHT, FIELD_WIDTH, NEXT_PIECES, TICKS_PER_SECOND},
+    tetromino::{Kind, Tetromino},
+};
+
+use crate::{
+    bindings::{Action, Bindings, Key},
+    config::Config,
+    game::phase::{
+        controls::Controls,
+        finished::Finished,
+        menu::{Entry, Menu},
+        online::Online,
+        playback::Playback,
+        scores::{Scores, MODES},
+        settings::{Setting, Settings},
+        versus::{Opponent, Outcome, Player, Versus},
+        Phase,
+    },
+    history::{sparkline, History},
+    net::{self, Snapshot},
+    scores::Score,
+    theme::Colors,
 };
 
 pub struct Ui {
@@ -41,8 +59,14 @@ impl Default for Ui {
 }
 
 const HEIGHT: u16 = FIELD_HEIGHT as u16 + 2;
-const LEVEL_WIDTH: u16 = 17;
+const LEVEL_WIDTH: u16 = 19;
 const GAME_WIDTH: u16 = FIELD_WIDTH as u16 * 2 + 2;
+const BOARD_WIDTH: u16 = LEVEL_WIDTH + GAME_WIDTH * 2;
+const MINI_WIDTH: u16 = FIELD_WIDTH as u16 + 2;
+const MENU_WIDTH: u16 = 36;
+const PERSONAL_BESTS: usize = 5;
+const SPARKLINE_GAMES: usize = 50;
+const NEXT_HEIGHT: usize = NEXT_PIECES * 3;
 
 fn left_area(offset: &Rect) -> Rect {
     Rect::new(offset.x, offset.y, LEVEL_WIDTH, HEIGHT)
@@ -62,11 +86,12 @@ fn right_area(offset: &Rect) -> Rect {
 }
 
 impl Ui {
-    pub fn draw(&mut self, phase: &Phase) {
+    // The config is not part of the hash, changing it always goes along with a new phase
+    pub fn draw(&mut self, phase: &Phase, config: &Config) {
         if self.should_render(phase) {
             let frame = self
                 .terminal
-                .draw(|frame| draw_frame(phase, frame))
+                .draw(|frame| draw_frame(phase, config, frame))
                 .unwrap();
             let mut hasher = DefaultHasher::new();
             phase.hash(&mut hasher);
@@ -79,30 +104,32 @@ impl Ui {
     fn should_render(&self, phase: &Phase) -> bool {
         let size_changed = self.terminal.size().unwrap() != self.previous_size;
 
-        // TODO: this will not work when changing from running to finished, as the hash does not change
-        match phase {
-            Phase::Menu(_) => size_changed,
-            _ => {
-                let mut hasher = DefaultHasher::new();
-                phase.hash(&mut hasher);
-                let hash = hasher.finish();
+        let mut hasher = DefaultHasher::new();
+        phase.hash(&mut hasher);
+        let hash = hasher.finish();
 
-                self.previous_hash != hash || size_changed
-            }
-        }
+        self.previous_hash != hash || size_changed
     }
 }
 
-fn draw_frame(phase: &Phase, frame: &mut tui::Frame<CrosstermBackend<Stdout>>) {
+pub fn draw_frame<B: Backend>(phase: &Phase, config: &Config, frame: &mut Frame<B>) {
     match phase {
-        Phase::Menu(_) => draw_menu(frame),
-        Phase::Running(running) => draw_tetrs(&running.state, frame),
-        Phase::Finished(finished) => draw_tetrs(&finished.state, frame),
+        Phase::Menu(menu) => draw_menu(menu, config, frame),
+        Phase::Running(running) => draw_tetrs(&running.state, config, frame),
+        Phase::Paused(paused) => draw_paused(&paused.running.state, config, frame),
+        Phase::Finished(finished) => draw_finished(finished, config, frame),
+        Phase::Versus(versus) => draw_versus(versus, config, frame),
+        Phase::Online(online) => draw_online(online, config, frame),
+        Phase::Playback(playback) => draw_playback(playback, config, frame),
+        Phase::Statistics(statistics) => draw_statistics(&statistics.history, frame),
+        Phase::Controls(controls) => draw_controls(controls, frame),
+        Phase::Scores(scores) => draw_high_scores(scores, frame),
+        Phase::Settings(settings) => draw_settings(settings, frame),
     };
 }
 
-fn centered(size: &Rect) -> Rect {
-    let width = min(size.width, LEVEL_WIDTH + GAME_WIDTH * 2);
+fn centered(size: &Rect, width: u16) -> Rect {
+    let width = min(size.width, width);
     let height = min(size.height, HEIGHT);
 
     let x = ((size.width - width) / 2).saturating_sub(1);
@@ -116,6 +143,17 @@ fn centered(size: &Rect) -> Rect {
     }
 }
 
+fn board_area(offset: &Rect, index: u16) -> Rect {
+    let area = Rect::new(
+        offset.x + BOARD_WIDTH * index,
+        offset.y,
+        BOARD_WIDTH,
+        HEIGHT,
+    );
+
+    intersect(&area, offset)
+}
+
 fn intersect(first: &Rect, second: &Rect) -> Rect {
     let x1 = max(first.x, second.x);
     let y1 = max(first.y, second.y);
@@ -130,59 +168,540 @@ fn intersect(first: &Rect, second: &Rect) -> Rect {
     }
 }
 
-fn draw_tetrs(state: &GameState, frame: &mut tui::Frame<CrosstermBackend<Stdout>>) {
-    let rect = centered(&frame.size());
+fn draw_tetrs<B: Backend>(state: &GameState, config: &Config, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+
+    draw_board(
+        state,
+        "tetrs",
+        draw_bindings(&config.keys),
+        &rect,
+        config,
+        frame,
+    );
+}
+
+fn draw_finished<B: Backend>(finished: &Finished, config: &Config, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+    draw_board(
+        &finished.state,
+        "tetrs",
+        draw_bindings(&config.keys),
+        &rect,
+        config,
+        frame,
+    );
+
+    let title = match (finished.record, finished.is_typing()) {
+        (_, true) => String::from("high score!"),
+        (Some(0), _) => String::from("new record!"),
+        (Some(rank), _) => format!("#{} high score", rank + 1),
+        (None, _) => String::from("game over"),
+    };
+
+    let state = &finished.state;
+    let stats = &state.stats;
+
+    let summary = [
+        ("Score", state.level.score.to_string()),
+        ("Lines", state.level.cleared_lines.to_string()),
+        ("Level", state.level.current.to_string()),
+        ("Pieces", state.pieces.to_string()),
+        ("Time", duration(state.seconds() as u64)),
+        ("PPS", format!("{:.2}", state.pieces_per_second())),
+        ("KPP", format!("{:.2}", state.keys_per_piece())),
+        ("APM", format!("{:.1}", state.attack_per_minute())),
+        ("Faults", stats.faults.to_string()),
+    ];
+
+    let mut lines = vec![Spans::from("")];
+    lines.extend(
+        summary
+            .iter()
+            .map(|(name, value)| Spans::from(format!("{name:<8}{value:>12}"))),
+    );
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(format!(
+        "Clears {}",
+        stats.clears.map(|x| x.to_string()).join(" / ")
+    )));
+    lines.push(Spans::from(distribution(stats.kinds)));
+    lines.push(Spans::from(""));
+
+    match &finished.initials {
+        Some(initials) => {
+            lines.push(Spans::from("Enter your initials"));
+            lines.push(Spans::from(format!("{initials:_<3}")));
+        }
+        None => {
+            lines.push(Spans::from("enter to retry, m for menu"));
+            lines.push(Spans::from(hint(&config.keys, Action::Quit, "quit")));
+        }
+    }
+
+    let rect = popup_area(&frame.size(), 30, lines.len() as u16 + 2);
+    let paragraph = Paragraph::new(lines)
+        .block(block(&title))
+        .alignment(Alignment::Center);
+
+    frame.render_widget(Clear, rect);
+    frame.render_widget(paragraph, rect);
+}
+
+// Hides the field and the preview, so that a pause cannot be used to plan ahead
+fn draw_paused<B: Backend>(state: &GameState, config: &Config, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+    draw_board(
+        state,
+        "tetrs",
+        draw_bindings(&config.keys),
+        &rect,
+        config,
+        frame,
+    );
 
-    let left_area = intersect(&left_area(&rect), &rect);
     let game_area = intersect(&game_area(&rect), &rect);
-    let right_area = intersect(&right_area(&rect), &rect);
+    let next_area = split_right_area(intersect(&right_area(&rect), &rect))[0];
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(right_area);
+    let mut lines = vec![Spans::from(""); (HEIGHT as usize - 2) / 2 - 1];
+    lines.push(Spans::from("paused"));
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(hint(&config.keys, Action::Pause, "resume")));
+
+    frame.render_widget(Clear, game_area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block("tetrs"))
+            .alignment(Alignment::Center),
+        game_area,
+    );
+    frame.render_widget(Clear, next_area);
+    frame.render_widget(block("next"), next_area);
+}
+
+fn draw_versus<B: Backend>(versus: &Versus, config: &Config, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH * 2);
+
+    let (titles, helps) = match &versus.opponent {
+        // The keys of the boards are fixed, restarting and quitting follow the bindings
+        Opponent::Human => (
+            [Player::One.name(), Player::Two.name()],
+            [&PLAYER_ONE_HELP, &PLAYER_TWO_HELP].map(|keys| {
+                let mut help = draw_help(keys);
+                help.extend(binding_rows(&config.keys, &[Action::Restart, Action::Quit]));
+
+                help
+            }),
+        ),
+        Opponent::Cpu(bot) => (
+            ["you", "cpu"],
+            [
+                draw_bindings(&config.keys),
+                draw_help(&[(" Speed", bot.speed.name())]),
+            ],
+        ),
+        // Suggestions of the bot that could not be played are counted once there are any
+        Opponent::External { name, unreachable } => (
+            ["you", name.as_str()],
+            [
+                draw_bindings(&config.keys),
+                match unreachable {
+                    0 => draw_help(&[]),
+                    count => draw_help(&[(" Invalid", &count.to_string())]),
+                },
+            ],
+        ),
+    };
+
+    for (index, (board, help)) in versus.boards.iter().zip(helps).enumerate() {
+        let area = board_area(&rect, index as u16);
+
+        draw_board(&board.state, titles[index], help, &area, config, frame);
+    }
+
+    if let Some(outcome) = versus.outcome {
+        let text = match (&versus.opponent, outcome) {
+            (_, Outcome::Draw) => String::from("draw!"),
+            (Opponent::Human, Outcome::Winner(winner)) => format!("{} wins!", winner.name()),
+            (_, Outcome::Winner(Player::One)) => String::from("you win!"),
+            (_, Outcome::Winner(Player::Two)) => format!("{} wins!", titles[1]),
+        };
+
+        let keys = [
+            hint(&config.keys, Action::Restart, "restart"),
+            hint(&config.keys, Action::Quit, "quit"),
+        ];
+        draw_popup(&text, &keys, frame);
+    }
+}
+
+fn draw_online<B: Backend>(online: &Online, config: &Config, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH + MINI_WIDTH);
+
+    draw_board(
+        &online.local.state,
+        "you",
+        draw_bindings(&config.keys),
+        &board_area(&rect, 0),
+        config,
+        frame,
+    );
+
+    let mini_area = Rect::new(rect.x + BOARD_WIDTH, rect.y, MINI_WIDTH, HEIGHT);
+    let lines = match &online.opponent {
+        Some(snapshot) => draw_snapshot(snapshot, &config.colors()),
+        None => vec![Spans::from("waiting")],
+    };
+
+    frame.render_widget(
+        Paragraph::new(lines).block(block("opponent")),
+        intersect(&mini_area, &rect),
+    );
+
+    if let Some(outcome) = online.outcome {
+        // A game over the network is played once
+        draw_popup(
+            outcome.text(),
+            &[hint(&config.keys, Action::Quit, "quit")],
+            frame,
+        );
+    }
+}
+
+fn draw_playback<B: Backend>(playback: &Playback, config: &Config, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+    let state = &playback.running.state;
+
+    let status = match (playback.over, playback.paused) {
+        (true, _) => "over",
+        (_, true) => "paused",
+        _ => playback.speed(),
+    };
+    let title = format!("{}/{} {status}", state.pieces, playback.pieces);
+
+    // Restarting plays the replay again from the start
+    let mut help = draw_help(&PLAYBACK_HELP);
+    help.extend(binding_rows(&config.keys, &[Action::Restart, Action::Quit]));
+
+    draw_board(state, &title, help, &rect, config, frame);
+}
+
+fn draw_statistics<B: Backend>(history: &History, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+
+    let mut lines = vec![
+        Spans::from(""),
+        Spans::from(format!(
+            " Games  {:>10}    Time   {:>10}",
+            history.games.len(),
+            hours(history.total_time())
+        )),
+        Spans::from(format!(
+            " Pieces {:>10}    Lines  {:>10}",
+            history.total_pieces(),
+            history.total_lines()
+        )),
+        Spans::from(format!(" PPS    {:>10.2}", history.average_pps())),
+        Spans::from(""),
+        Spans::from(" Personal bests"),
+    ];
+
+    for mode in history.modes() {
+        let bests = history.personal_bests(mode);
+        let skip = bests.len().saturating_sub(PERSONAL_BESTS);
+
+        lines.extend(bests.iter().skip(skip).map(|game| {
+            Spans::from(format!(
+                " {:<10} {}  {:>8}",
+                mode.name(),
+                date(game.date),
+                game.score
+            ))
+        }));
+    }
+
+    let recent = history.recent_pps(SPARKLINE_GAMES);
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(format!(
+        " PPS of the last {} games",
+        recent.len()
+    )));
+    lines.push(Spans::from(format!(" {}", sparkline(&recent))));
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(" esc to go back"));
+
+    frame.render_widget(Paragraph::new(lines).block(block("statistics")), rect);
+}
+
+fn draw_controls<B: Backend>(controls: &Controls, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+
+    let mut lines = vec![Spans::from("")];
+    lines.extend(Action::ALL.iter().enumerate().map(|(index, &action)| {
+        let keys = match (index == controls.selected, controls.capture) {
+            (true, Some(_)) => String::from("..."),
+            _ => labels(controls.bindings.get(action)),
+        };
+        let style = match index == controls.selected {
+            true => Style::default().add_modifier(Modifier::REVERSED),
+            false => Style::default(),
+        };
+
+        Spans::from(Span::styled(format!(" {:<8} {keys}", action.name()), style))
+    }));
+    lines.push(Spans::from(""));
+
+    match (controls.capture, &controls.error) {
+        (Some(_), _) => lines.push(Spans::from(format!(
+            " Press a key for {}",
+            controls.action().name()
+        ))),
+        (None, Some(error)) => lines.push(Spans::from(format!(" {error}"))),
+        (None, None) => {
+            lines.push(Spans::from(" ↑ ↓ to choose an action"));
+            lines.push(Spans::from(" enter to replace its keys, a to add one"));
+            lines.push(Spans::from(" r to reset, esc to go back"));
+        }
+    }
+
+    frame.render_widget(Paragraph::new(lines).block(block("controls")), rect);
+}
+
+fn draw_settings<B: Backend>(settings: &Settings, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+    let config = &settings.config;
+
+    let mut lines = vec![Spans::from("")];
+    lines.extend(Setting::ALL.iter().map(|&setting| {
+        let value = match setting {
+            Setting::Ghost => on_off(config.ghost),
+            Setting::Next => config.next.to_string(),
+            Setting::Das => format!("{} ms", config.das),
+            Setting::Arr => format!("{} ms", config.arr),
+            Setting::Theme => config.theme.clone(),
+            Setting::Grid => on_off(config.grid),
+            Setting::Sound => on_off(config.sound),
+        };
+        let style = match setting == settings.setting() {
+            true => Style::default().add_modifier(Modifier::REVERSED),
+            false => Style::default(),
+        };
+
+        Spans::from(Span::styled(
+            format!(" {:<12} < {value} >", setting.name()),
+            style,
+        ))
+    }));
+    lines.push(Spans::from(""));
+    lines.push(draw_swatch(&config.colors()));
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(" ↑ ↓ to choose a setting, ← → to change it"));
+    lines.push(Spans::from(" esc to go back"));
+
+    frame.render_widget(Paragraph::new(lines).block(block("settings")), rect);
+}
+
+// Every color of the theme next to each other
+fn draw_swatch(colors: &Colors) -> Spans<'static> {
+    let mut spans = vec![Span::raw(" ")];
+    spans.extend(
+        [
+            Kind::I,
+            Kind::O,
+            Kind::T,
+            Kind::S,
+            Kind::Z,
+            Kind::J,
+            Kind::L,
+        ]
+        .map(|kind| colors.kind(kind))
+        .into_iter()
+        .chain([colors.garbage])
+        .map(|color| Span::styled("  ", Style::default().bg(color))),
+    );
+
+    Spans::from(spans)
+}
+
+fn on_off(value: bool) -> String {
+    String::from(match value {
+        true => "on",
+        false => "off",
+    })
+}
+
+fn hours(seconds: u64) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// Formats seconds since the epoch as a date in UTC, see
+// https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn date(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year}-{month:02}-{day:02}")
+}
+
+fn draw_snapshot(snapshot: &Snapshot, colors: &Colors) -> Vec<Spans<'static>> {
+    snapshot
+        .cells
+        .chunks(FIELD_WIDTH)
+        .map(|line| {
+            Spans::from(
+                line.iter()
+                    .map(|&code| match colors.square(&net::square(code)) {
+                        Some(color) => Span::styled(" ", Style::default().bg(color)),
+                        None => Span::raw(" "),
+                    })
+                    .collect::<Vec<Span>>(),
+            )
+        })
+        .collect()
+}
+
+fn draw_board<B: Backend>(
+    state: &GameState,
+    title: &str,
+    help: Vec<Row<'static>>,
+    rect: &Rect,
+    config: &Config,
+    frame: &mut Frame<B>,
+) {
+    let left_area = intersect(&left_area(rect), rect);
+    let game_area = intersect(&game_area(rect), rect);
+    let right_area = intersect(&right_area(rect), rect);
+
+    let chunks = split_right_area(right_area);
 
     let stats = block("stats");
-    let game = block("tetrs");
+    let game = block(title);
     let next = block("next");
-    let help = block("help");
 
     let line_vec: Vec<Line> = std::iter::repeat(Line::default())
         .take(FIELD_HEIGHT)
         .collect();
     let mut lines: [Line; FIELD_HEIGHT] = line_vec.try_into().unwrap();
 
-    let next_lines_vec: Vec<Line> = std::iter::repeat(Line::default()).take(6).collect();
-    let mut next_lines: [Line; 6] = next_lines_vec.try_into().unwrap();
+    let mut next_lines: Vec<Line> = std::iter::repeat(Line::default())
+        .take(NEXT_HEIGHT)
+        .collect();
 
-    let game_paragraph = Paragraph::new(draw_field(state, &mut lines)).block(game);
-    let stats_paragraph = Table::new(draw_stats(&state.level))
+    let game_paragraph = Paragraph::new(draw_field(state, config, &mut lines)).block(game);
+    let stats_paragraph = Table::new(draw_stats(state))
         .block(stats)
-        .widths(&[Constraint::Length(7), Constraint::Length(15)]);
-    let next_paragraph = Paragraph::new(draw_next(&state.next, &mut next_lines)).block(next);
-    let help_table = Table::new(draw_help())
-        .block(help)
+        .widths(&[Constraint::Length(8), Constraint::Length(8)]);
+    let next_paragraph = Paragraph::new(draw_next(state, config, &mut next_lines)).block(next);
+    let help_table = Table::new(help)
+        .block(block("help"))
         .widths(&[Constraint::Length(8), Constraint::Length(15)]);
 
     frame.render_widget(stats_paragraph, left_area);
     frame.render_widget(game_paragraph, game_area);
     frame.render_widget(next_paragraph, chunks[0]);
     frame.render_widget(help_table, chunks[1]);
+
+    draw_garbage_meter(state.garbage.total(), &game_area, frame);
+}
+
+fn split_right_area(area: Rect) -> Vec<Rect> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area)
 }
 
-fn block(title: &str) -> Block {
+// The meter replaces the left border of the field, growing from the bottom
+fn draw_garbage_meter<B: Backend>(pending: u32, area: &Rect, frame: &mut Frame<B>) {
+    let height = min(
+        pending as u16,
+        min(FIELD_HEIGHT as u16, area.height.saturating_sub(2)),
+    );
+
+    if height == 0 || area.width == 0 {
+        return;
+    }
+
+    let meter = Rect::new(area.x, area.y + area.height - 1 - height, 1, height);
+    let lines =
+        vec![Spans::from(Span::styled(" ", Style::default().bg(Color::Red))); height as usize];
+
+    frame.render_widget(Paragraph::new(lines), meter);
+}
+
+fn popup_area(size: &Rect, width: u16, height: u16) -> Rect {
+    let width = min(size.width, width);
+    let height = min(size.height, height);
+
+    Rect::new(
+        (size.width - width) / 2,
+        (size.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+// Every key hint gets a line of its own
+fn draw_popup<B: Backend>(text: &str, keys: &[String], frame: &mut Frame<B>) {
+    let rect = popup_area(&frame.size(), 30, keys.len() as u16 + 5);
+
+    let mut lines = vec![
+        Spans::from(""),
+        Spans::from(text.to_string()),
+        Spans::from(""),
+    ];
+    lines.extend(keys.iter().map(|x| Spans::from(x.clone())));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block("game over"))
+        .alignment(Alignment::Center);
+
+    frame.render_widget(Clear, rect);
+    frame.render_widget(paragraph, rect);
+}
+
+fn block(title: &str) -> Block<'_> {
     Block::default()
         .title(title)
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center)
 }
 
-fn draw_field<'a>(state: &GameState, rows: &'a mut [Line; FIELD_HEIGHT]) -> Vec<Spans<'a>> {
-    if let Some(preview) = &state.preview {
-        draw_tetromino(preview, rows, Cell::preview(preview));
+fn draw_field<'a>(
+    state: &GameState,
+    config: &Config,
+    rows: &'a mut [Line; FIELD_HEIGHT],
+) -> Vec<Spans<'a>> {
+    let colors = &config.colors();
+
+    if config.grid {
+        for row in rows.iter_mut() {
+            row.cells.fill(Cell::grid());
+        }
+    }
+
+    if let Some(preview) = state.preview.as_ref().filter(|_| config.ghost) {
+        draw_tetromino(preview, rows, Cell::preview(preview, colors));
     }
-    draw_tetromino(&state.current, rows, Cell::normal(&state.current));
-    draw_solidified(&state.field, rows);
+    draw_tetromino(&state.current, rows, Cell::normal(&state.current, colors));
+    draw_solidified(&state.field, colors, rows);
 
     rows.iter().map(|x| x.to_spans()).collect()
 }
@@ -194,47 +713,146 @@ fn draw_tetromino(tetromino: &Tetromino, rows: &mut [Line], cell: Cell) {
     }
 }
 
-fn draw_solidified(field: &Field, rows: &mut [Line; FIELD_HEIGHT]) {
+fn draw_solidified(field: &Field, colors: &Colors, rows: &mut [Line; FIELD_HEIGHT]) {
     for (line_index, line) in field.iter().enumerate() {
         for (column_index, square) in line.iter().enumerate() {
-            if let Square::Occupied(color) = square {
+            if let Some(color) = colors.square(square) {
                 rows[line_index].cells[column_index] = Cell {
                     str: "  ",
-                    style: Style::default().bg(*color),
+                    style: Style::default().bg(color),
                 };
             }
         }
     }
 }
 
-fn draw_next<'a>(next: &Tetromino, rows: &'a mut [Line; 6]) -> Vec<Spans<'a>> {
-    draw_tetromino(next, rows, Cell::normal(next));
+// Every piece of the queue gets three rows, which fits the tallest spawn orientation
+fn draw_next<'a>(state: &GameState, config: &Config, rows: &'a mut [Line]) -> Vec<Spans<'a>> {
+    for (index, next) in state.upcoming().take(config.next).enumerate() {
+        let mut next = next.clone();
+        next.coords.y += 3 * index as i32;
+
+        draw_tetromino(&next, rows, Cell::normal(&next, &config.colors()));
+    }
 
     rows.iter().map(|x| x.to_spans()).collect()
 }
 
-fn draw_help() -> Vec<Row<'static>> {
-    vec![
-        Row::new(vec!["", ""]),
-        Row::new(vec![" Left", "←"]),
-        Row::new(vec![" Right", "→"]),
-        Row::new(vec![" Down", "↓"]),
-        Row::new(vec![" Rotate", "↑"]),
-        Row::new(vec![" Drop", "d, space"]),
-        Row::new(vec![" Restart", "r"]),
-        Row::new(vec![" Quit", "q, ctrl+c"]),
-    ]
+const PLAYBACK_HELP: [(&str, &str); 6] = [
+    (" Pause", "space"),
+    (" Faster", "+"),
+    (" Slower", "-"),
+    (" Step", "."),
+    (" Seek", "← →"),
+    (" Seek 10", "↓ ↑"),
+];
+
+const PLAYER_ONE_HELP: [(&str, &str); 5] = [
+    (" Left", "a"),
+    (" Right", "d"),
+    (" Down", "s"),
+    (" Rotate", "w"),
+    (" Drop", "space"),
+];
+
+const PLAYER_TWO_HELP: [(&str, &str); 5] = [
+    (" Left", "←"),
+    (" Right", "→"),
+    (" Down", "↓"),
+    (" Rotate", "↑"),
+    (" Drop", "enter"),
+];
+
+fn draw_help(keys: &[(&str, &str)]) -> Vec<Row<'static>> {
+    let mut rows = vec![Row::new(vec!["", ""])];
+    rows.extend(
+        keys.iter()
+            .map(|&(action, key)| Row::new(vec![action.to_string(), key.to_string()])),
+    );
+
+    rows
+}
+
+// The help of single player games follows the key bindings of the config
+fn draw_bindings(bindings: &Bindings) -> Vec<Row<'static>> {
+    let mut rows = vec![Row::new(vec!["", ""])];
+    rows.extend(binding_rows(bindings, &Action::ALL));
+
+    rows
+}
+
+fn binding_rows(bindings: &Bindings, actions: &[Action]) -> Vec<Row<'static>> {
+    actions
+        .iter()
+        .map(|&action| {
+            Row::new(vec![
+                format!(" {}", action.name()),
+                labels(bindings.get(action)),
+            ])
+        })
+        .collect()
 }
 
-fn draw_stats(level: &Level) -> Vec<Row<'static>> {
+// E.g. "q, ctrl+c to quit"
+fn hint(bindings: &Bindings, action: Action, what: &str) -> String {
+    format!("{} to {what}", labels(bindings.get(action)))
+}
+
+fn labels(keys: &[Key]) -> String {
+    keys.iter()
+        .map(Key::label)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn draw_stats(state: &GameState) -> Vec<Row<'static>> {
+    let level = &state.level;
+    let stats = &state.stats;
+    let [singles, doubles, triples, tetrises] = stats.clears;
+    let [i, o, t, s, z, j, l] = stats.kinds;
+
     vec![
         Row::new(vec![String::from(""), String::from("")]),
+        Row::new(vec![String::from(" Mode:"), state.mode.name().into()]),
         Row::new(vec![" Level:".into(), format!("{}", level.current)]),
         Row::new(vec![" Lines:".into(), format!("{}", level.cleared_lines)]),
         Row::new(vec![" Score:".into(), format!("{}", level.score)]),
+        Row::new(vec![String::from(""), String::from("")]),
+        Row::new(vec![
+            " PPS:".into(),
+            format!("{:.2}", state.pieces_per_second()),
+        ]),
+        Row::new(vec![
+            " KPP:".into(),
+            format!("{:.2}", state.keys_per_piece()),
+        ]),
+        Row::new(vec![
+            " APM:".into(),
+            format!("{:.1}", state.attack_per_minute()),
+        ]),
+        Row::new(vec![" Faults:".into(), format!("{}", stats.faults)]),
+        Row::new(vec![String::from(""), String::from("")]),
+        Row::new(vec![" Single:".into(), format!("{singles}")]),
+        Row::new(vec![" Double:".into(), format!("{doubles}")]),
+        Row::new(vec![" Triple:".into(), format!("{triples}")]),
+        Row::new(vec![" Tetris:".into(), format!("{tetrises}")]),
+        Row::new(vec![String::from(""), String::from("")]),
+        Row::new(vec![format!(" I {i:>4}"), format!("O {o:>4}")]),
+        Row::new(vec![format!(" T {t:>4}"), format!("S {s:>4}")]),
+        Row::new(vec![format!(" Z {z:>4}"), format!("J {j:>4}")]),
+        Row::new(vec![format!(" L {l:>4}"), String::from("")]),
     ]
 }
 
+fn distribution(kinds: [u32; 7]) -> String {
+    ["I", "O", "T", "S", "Z", "J", "L"]
+        .iter()
+        .zip(kinds)
+        .map(|(name, count)| format!("{name}{count}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 #[derive(Clone, Debug)]
 struct Line {
     pub cells: [Cell; FIELD_WIDTH],
@@ -253,7 +871,7 @@ impl Default for Line {
 }
 
 impl Line {
-    fn to_spans(&self) -> Spans {
+    fn to_spans(&self) -> Spans<'_> {
         Spans::from(
             self.cells
                 .iter()
@@ -279,61 +897,155 @@ impl Default for Cell {
 }
 
 impl Cell {
-    fn to_span(&self) -> Span {
+    fn to_span(&self) -> Span<'_> {
         Span::styled(self.str, self.style)
     }
 
-    fn normal(tetromino: &Tetromino) -> Self {
+    fn normal(tetromino: &Tetromino, colors: &Colors) -> Self {
         Self {
             str: "  ",
-            style: Style::default().bg(tetromino.color),
+            style: Style::default().bg(colors.kind(tetromino.kind)),
         }
     }
 
-    fn preview(tetromino: &Tetromino) -> Self {
+    fn grid() -> Self {
+        Self {
+            str: " .",
+            style: Style::default().fg(Color::DarkGray),
+        }
+    }
+
+    fn preview(tetromino: &Tetromino, colors: &Colors) -> Self {
         Self {
             str: "◤◢",
-            style: Style::default().fg(tetromino.color),
+            style: Style::default().fg(colors.kind(tetromino.kind)),
         }
     }
 }
 
-fn draw_menu(frame: &mut tui::Frame<CrosstermBackend<Stdout>>) {
-    let vertical_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage(20),
-            Constraint::Max(u16::MAX),
-            Constraint::Percentage(20),
-        ])
-        .split(frame.size());
-
-    let horizontal_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(20),
-            Constraint::Max(u16::MAX),
-            Constraint::Percentage(20),
-        ])
-        .split(vertical_chunks[1]);
-
-    let rect = horizontal_chunks[1];
-    let lines = get_menu_lines(&rect);
-
-    let block = block("tetrs");
+fn draw_menu<B: Backend>(menu: &Menu, config: &Config, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+
+    draw_board(
+        &menu.demo.running.state,
+        "demo",
+        draw_bindings(&config.keys),
+        &rect,
+        config,
+        frame,
+    );
+
+    let lines = get_menu_lines(menu);
+    let rect = popup_area(&frame.size(), MENU_WIDTH, lines.len() as u16 + 2);
 
     let paragraph = Paragraph::new(lines)
-        .block(block)
-        .alignment(tui::layout::Alignment::Center)
-        .wrap(Wrap { trim: true });
+        .block(block("tetrs"))
+        .alignment(tui::layout::Alignment::Center);
 
+    frame.render_widget(Clear, rect);
     frame.render_widget(paragraph, rect);
 }
 
-fn get_menu_lines(rect: &Rect) -> Vec<Spans> {
-    let empty_lines = (rect.height / 2).saturating_sub(1);
-    let mut lines = vec![Spans::from(""); empty_lines.into()];
-    lines.push(Spans::from("Choose a level (0-9)"));
+fn draw_high_scores<B: Backend>(scores: &Scores, frame: &mut Frame<B>) {
+    let rect = centered(&frame.size(), BOARD_WIDTH);
+
+    // The mode can only be changed when there is more than one table
+    let (mode, hint) = match MODES.len() {
+        1 => (format!(" {}", scores.mode.name()), " esc to go back"),
+        _ => (
+            format!(" < {} >", scores.mode.name()),
+            " ← → to change the mode, esc to go back",
+        ),
+    };
+
+    let mut lines = vec![
+        Spans::from(""),
+        Spans::from(match scores.tick_rate {
+            TICKS_PER_SECOND => mode,
+            rate => format!("{mode} at {rate} ticks per second"),
+        }),
+        Spans::from(""),
+    ];
+
+    let top: Vec<&Score> = scores.scores.top(scores.mode, scores.tick_rate).collect();
+
+    match top.is_empty() {
+        true => lines.push(Spans::from(" No games yet")),
+        false => lines.push(Spans::from(
+            "     Name        Score Lines Level   Time  Date",
+        )),
+    }
+
+    lines.extend(top.iter().enumerate().map(|(index, score)| {
+        Spans::from(format!(
+            " {:>2}. {:<8} {:>8} {:>5} {:>5} {:>6}  {}",
+            index + 1,
+            score.name.chars().take(8).collect::<String>(),
+            score.score,
+            score.lines,
+            score.level,
+            duration(score.seconds()),
+            date(score.date)
+        ))
+    }));
+
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(hint));
+
+    frame.render_widget(Paragraph::new(lines).block(block("high scores")), rect);
+}
+
+pub fn duration(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn get_menu_lines(menu: &Menu) -> Vec<Spans<'static>> {
+    let mut lines = vec![Spans::from("")];
+
+    lines.extend(menu.entries().into_iter().map(|entry| {
+        let value = match entry {
+            Entry::Resume => menu.saved.as_ref().map(|saved| {
+                format!(
+                    "{} ({})",
+                    saved.state.mode.name(),
+                    saved.state.level.current
+                )
+            }),
+            Entry::Mode => Some(format!("< {} >", menu.mode.name())),
+            Entry::Bot => Some(format!("< {} >", menu.speed.name())),
+            Entry::Level => Some(format!("< {} >", menu.level)),
+            _ => None,
+        };
+        let name = match entry {
+            Entry::Level if menu.mode.is_endless() => "Speed",
+            _ => entry.name(),
+        };
+        let style = match entry == menu.selected {
+            true => Style::default().add_modifier(Modifier::REVERSED),
+            false => Style::default(),
+        };
+
+        let text = format!(" {name:<12}{:>14} ", value.unwrap_or_default());
+        Spans::from(Span::styled(text, style))
+    }));
+
+    lines.push(Spans::from(""));
+    lines.push(Spans::from("↑ ↓ to choose, ← → to change"));
 
     lines
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_in_utc() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951782400), "2000-02-29");
+        assert_eq!(date(1709251199), "2024-02-29");
+        assert_eq!(date(1735689600), "2025-01-01");
+        // 2100 is not a leap year
+        assert_eq!(date(4107542400), "2100-03-01");
+    }
+}
@@ -0,0 +1,149 @@
+use crate::input::Input;
+
+use super::{
+    placement::Placement,
+    state::{GameState, Square, FIELD_HEIGHT, FIELD_WIDTH},
+    tetromino::Tetromino,
+};
+
+const HEIGHT_WEIGHT: f64 = -0.51;
+const LINES_WEIGHT: f64 = 0.76;
+const HOLES_WEIGHT: f64 = -0.36;
+const BUMPINESS_WEIGHT: f64 = -0.18;
+const WELLS_WEIGHT: f64 = -0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Speed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl Speed {
+    pub const fn next(self) -> Self {
+        match self {
+            Speed::Slow => Speed::Normal,
+            Speed::Normal => Speed::Fast,
+            Speed::Fast => Speed::Slow,
+        }
+    }
+
+    pub const fn previous(self) -> Self {
+        match self {
+            Speed::Slow => Speed::Fast,
+            Speed::Normal => Speed::Slow,
+            Speed::Fast => Speed::Normal,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Speed::Slow => "slow",
+            Speed::Normal => "normal",
+            Speed::Fast => "fast",
+        }
+    }
+
+    const fn ticks_per_input(&self) -> u32 {
+        match self {
+            Speed::Slow => 20,
+            Speed::Normal => 8,
+            Speed::Fast => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bot {
+    pub speed: Speed,
+    ticks: u32,
+    piece: Option<u32>,
+    // The squares the current piece is headed for
+    target: Option<Vec<(i32, i32)>>,
+}
+
+impl Bot {
+    pub fn new(speed: Speed) -> Self {
+        Self {
+            speed,
+            ticks: 0,
+            piece: None,
+            target: None,
+        }
+    }
+
+    // Returns at most one input per call and waits between them according to the speed
+    pub fn next_input(&mut self, state: &GameState) -> Option<Input> {
+        self.ticks += 1;
+
+        if self.ticks < self.speed.ticks_per_input() {
+            return None;
+        }
+
+        self.ticks = 0;
+
+        if self.piece != Some(state.pieces) {
+            self.piece = Some(state.pieces);
+            self.target = None;
+        }
+
+        // A target that gravity has taken out of reach is given up for the best one left
+        let placement = match self.target.as_deref().and_then(|x| state.placement_at(x)) {
+            Some(placement) => Some(placement),
+            None => best_placement(state),
+        };
+        self.target = placement.as_ref().map(Placement::squares);
+
+        placement.and_then(|x| x.path.first().copied())
+    }
+}
+
+pub fn best_placement(state: &GameState) -> Option<Placement> {
+    state
+        .placements()
+        .into_iter()
+        .map(|placement| (evaluate(state, &placement.tetromino), placement))
+        .max_by(|(first, _), (second, _)| first.total_cmp(second))
+        .map(|(_, placement)| placement)
+}
+
+pub fn evaluate(state: &GameState, tetromino: &Tetromino) -> f64 {
+    let (field, lines) = state.place(tetromino);
+
+    let heights: Vec<usize> = (0..FIELD_WIDTH)
+        .map(|column| {
+            field
+                .iter()
+                .position(|line| line[column] != Square::Empty)
+                .map_or(0, |row| field.len() - row)
+        })
+        .collect();
+
+    let holes: usize = (0..FIELD_WIDTH)
+        .map(|column| {
+            field
+                .iter()
+                .rev()
+                .take(heights[column])
+                .filter(|line| line[column] == Square::Empty)
+                .count()
+        })
+        .sum();
+
+    let bumpiness: usize = heights.windows(2).map(|x| x[0].abs_diff(x[1])).sum();
+
+    let wells: usize = (0..FIELD_WIDTH)
+        .map(|column| {
+            let left = column.checked_sub(1).map_or(FIELD_HEIGHT, |x| heights[x]);
+            let right = heights.get(column + 1).copied().unwrap_or(FIELD_HEIGHT);
+
+            left.min(right).saturating_sub(heights[column])
+        })
+        .sum();
+
+    HEIGHT_WEIGHT * heights.iter().sum::<usize>() as f64
+        + LINES_WEIGHT * lines as f64
+        + HOLES_WEIGHT * holes as f64
+        + BUMPINESS_WEIGHT * bumpiness as f64
+        + WELLS_WEIGHT * wells as f64
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::level::ClearedLines;
+
+const COMBO_TABLE: [u32; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+const BACK_TO_BACK_BONUS: u32 = 1;
+const PERFECT_CLEAR_BONUS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spin {
+    None,
+    TSpin,
+}
+
+// Keeps track of everything that influences how many lines a clear sends to the opponent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Attack {
+    pub combo: Option<u32>,
+    pub back_to_back: bool,
+}
+
+impl Attack {
+    pub fn calculate(&mut self, cleared: &ClearedLines, spin: Spin, perfect_clear: bool) -> u32 {
+        if matches!(cleared, ClearedLines::None) {
+            self.combo = None;
+            return 0;
+        }
+
+        let combo = self.combo.map_or(0, |x| x + 1);
+        self.combo = Some(combo);
+
+        let difficult = spin == Spin::TSpin || matches!(cleared, ClearedLines::Tetrs);
+        let back_to_back = difficult && self.back_to_back;
+        self.back_to_back = difficult;
+
+        let mut lines = Self::lines(cleared, spin);
+        lines += COMBO_TABLE[(combo as usize).min(COMBO_TABLE.len() - 1)];
+
+        if back_to_back {
+            lines += BACK_TO_BACK_BONUS;
+        }
+
+        if perfect_clear {
+            lines += PERFECT_CLEAR_BONUS;
+        }
+
+        lines
+    }
+
+    const fn lines(cleared: &ClearedLines, spin: Spin) -> u32 {
+        match (spin, cleared) {
+            (_, ClearedLines::None) => 0,
+            (Spin::None, ClearedLines::Single) => 0,
+            (Spin::None, ClearedLines::Double) => 1,
+            (Spin::None, ClearedLines::Triple) => 2,
+            (Spin::None, ClearedLines::Tetrs) => 4,
+            (Spin::TSpin, ClearedLines::Single) => 2,
+            (Spin::TSpin, ClearedLines::Double) => 4,
+            (Spin::TSpin, ClearedLines::Triple) => 6,
+            (Spin::TSpin, ClearedLines::Tetrs) => 6,
+        }
+    }
+}
+
+// Garbage that was sent by the opponent but has not yet been pushed into the field.
+// Every entry is one attack and ends up as a block of lines sharing the same hole.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Garbage {
+    pending: VecDeque<u32>,
+}
+
+impl Garbage {
+    pub fn receive(&mut self, lines: u32) {
+        if lines > 0 {
+            self.pending.push_back(lines);
+        }
+    }
+
+    // Uses an attack to cancel pending garbage first and returns what is left to send
+    pub fn cancel(&mut self, mut attack: u32) -> u32 {
+        while attack > 0 {
+            let Some(first) = self.pending.front_mut() else {
+                break;
+            };
+
+            let cancelled = attack.min(*first);
+            *first -= cancelled;
+            attack -= cancelled;
+
+            if *first == 0 {
+                self.pending.pop_front();
+            }
+        }
+
+        attack
+    }
+
+    pub fn release(&mut self) -> Vec<u32> {
+        self.pending.drain(..).collect()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.pending.iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLEARS: [ClearedLines; 5] = [
+        ClearedLines::None,
+        ClearedLines::Single,
+        ClearedLines::Double,
+        ClearedLines::Triple,
+        ClearedLines::Tetrs,
+    ];
+
+    fn attacks(attack: &mut Attack, clears: &[ClearedLines]) -> Vec<u32> {
+        clears
+            .iter()
+            .map(|x| attack.calculate(x, Spin::None, false))
+            .collect()
+    }
+
+    #[test]
+    fn attack_table() {
+        let lines = |spin| CLEARS.map(|x| Attack::default().calculate(&x, spin, false));
+
+        assert_eq!(lines(Spin::None), [0, 0, 1, 2, 4]);
+        assert_eq!(lines(Spin::TSpin), [0, 2, 4, 6, 6]);
+    }
+
+    #[test]
+    fn combo_grows_until_a_piece_clears_nothing() {
+        let mut attack = Attack::default();
+
+        let singles: Vec<u32> = (0..13)
+            .map(|_| attack.calculate(&ClearedLines::Single, Spin::None, false))
+            .collect();
+        assert_eq!(singles, [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5, 5]);
+
+        let broken = attacks(&mut attack, &[ClearedLines::None, ClearedLines::Double]);
+        assert_eq!(broken, [0, 1]);
+        assert_eq!(attack.combo, Some(0));
+    }
+
+    // Pieces that clear nothing keep the back to back, easier clears end it
+    #[test]
+    fn back_to_back_difficult_clears() {
+        let mut attack = Attack::default();
+        let clears = [
+            ClearedLines::Tetrs,
+            ClearedLines::None,
+            ClearedLines::Tetrs,
+            ClearedLines::None,
+            ClearedLines::Double,
+            ClearedLines::None,
+            ClearedLines::Tetrs,
+        ];
+
+        assert_eq!(attacks(&mut attack, &clears), [4, 0, 5, 0, 1, 0, 4]);
+
+        attack.calculate(&ClearedLines::None, Spin::None, false);
+        assert_eq!(
+            attack.calculate(&ClearedLines::Single, Spin::TSpin, false),
+            3
+        );
+    }
+
+    #[test]
+    fn perfect_clear_bonus() {
+        let mut attack = Attack::default();
+
+        assert_eq!(
+            attack.calculate(&ClearedLines::Single, Spin::None, true),
+            10
+        );
+        assert_eq!(attack.calculate(&ClearedLines::Tetrs, Spin::None, true), 14);
+    }
+
+    #[test]
+    fn cancel_pending_attacks_in_order() {
+        let mut garbage = Garbage::default();
+        garbage.receive(3);
+        garbage.receive(0);
+        garbage.receive(2);
+        garbage.receive(4);
+
+        // The first attack is cancelled whole and the second one in part
+        assert_eq!(garbage.cancel(4), 0);
+        assert_eq!(garbage.total(), 5);
+        assert_eq!(garbage.clone().release(), [1, 4]);
+
+        // What is left over after the last attack is sent
+        assert_eq!(garbage.cancel(7), 2);
+        assert_eq!(garbage.total(), 0);
+        assert!(garbage.release().is_empty());
+    }
+}
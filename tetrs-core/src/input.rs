@@ -0,0 +1,29 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// The moves of the current piece, everything around the game is up to the frontend
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Input {
+    Left,
+    Right,
+    Down,
+    Rotate,
+    Drop,
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Input::Left),
+            "right" => Ok(Input::Right),
+            "down" => Ok(Input::Down),
+            "rotate" => Ok(Input::Rotate),
+            "drop" => Ok(Input::Drop),
+            _ => Err(format!("Unknown input {s}")),
+        }
+    }
+}
@@ -1,18 +1,30 @@
+use serde::{Deserialize, Serialize};
 use std::cmp::{max, min};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Level {
+    pub start: u32,
     pub current: u32,
     pub score: u32,
     pub cleared_lines: u32,
+    pub fixed: bool,
 }
 
 impl Level {
     pub fn new(level: u32) -> Self {
         Self {
+            start: level,
             current: level,
             score: 0,
             cleared_lines: 0,
+            fixed: false,
+        }
+    }
+
+    pub fn fixed(level: u32) -> Self {
+        Self {
+            fixed: true,
+            ..Self::new(level)
         }
     }
 
@@ -47,7 +59,7 @@ impl Level {
         self.cleared_lines += cleared.value();
         self.score += self.score(cleared);
 
-        if self.cleared_lines >= self.required_lines() {
+        if !self.fixed && self.cleared_lines >= self.required_lines() {
             self.current += 1;
         }
     }
@@ -0,0 +1,12 @@
+pub mod bot;
+pub mod garbage;
+pub mod input;
+pub mod level;
+pub mod math;
+pub mod mode;
+pub mod placement;
+pub mod replay;
+pub mod script;
+pub mod state;
+pub mod stats;
+pub mod tetromino;
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 pub struct Matrix2D {
     pub x0: i32,
     pub x1: i32,
@@ -19,7 +21,7 @@ pub const ROTATE_CW: Matrix2D = Matrix2D {
     y1: 0,
 };
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Vector2D {
     pub x: i32,
     pub y: i32,
@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Marathon,
+    Zen,
+    Versus,
+    Cpu,
+}
+
+impl Mode {
+    pub const fn next(self) -> Self {
+        match self {
+            Mode::Marathon => Mode::Zen,
+            Mode::Zen => Mode::Versus,
+            Mode::Versus => Mode::Cpu,
+            Mode::Cpu => Mode::Marathon,
+        }
+    }
+
+    pub const fn previous(self) -> Self {
+        match self {
+            Mode::Marathon => Mode::Cpu,
+            Mode::Zen => Mode::Marathon,
+            Mode::Versus => Mode::Zen,
+            Mode::Cpu => Mode::Versus,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Mode::Marathon => "marathon",
+            Mode::Zen => "zen",
+            Mode::Versus => "versus",
+            Mode::Cpu => "versus cpu",
+        }
+    }
+
+    // In zen mode topping out only clears the board and the speed stays where the player put it
+    pub const fn is_endless(&self) -> bool {
+        match self {
+            Mode::Marathon | Mode::Versus | Mode::Cpu => false,
+            Mode::Zen => true,
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "marathon" => Ok(Mode::Marathon),
+            "zen" => Ok(Mode::Zen),
+            "versus" => Ok(Mode::Versus),
+            "cpu" => Ok(Mode::Cpu),
+            _ => Err(format!("Unknown mode {s}")),
+        }
+    }
+}
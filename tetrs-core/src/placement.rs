@@ -0,0 +1,198 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::input::Input;
+
+use super::{
+    math::Vector2D,
+    state::GameState,
+    tetromino::{Rotation, Tetromino},
+};
+
+const MOVES: [Input; 4] = [Input::Left, Input::Right, Input::Rotate, Input::Down];
+
+// A position in which the current piece can be locked and the shortest input sequence to get
+// there, which always ends with a drop
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub tetromino: Tetromino,
+    pub path: Vec<Input>,
+}
+
+impl Placement {
+    pub fn squares(&self) -> Vec<(i32, i32)> {
+        let mut squares: Vec<(i32, i32)> = self
+            .tetromino
+            .offset_blocks()
+            .iter()
+            .map(|x| (x.vec.x, x.vec.y))
+            .collect();
+        squares.sort();
+
+        squares
+    }
+}
+
+impl GameState {
+    // Searches all positions the current piece can be moved to, including the ones that can only be
+    // reached by soft dropping first and moving or rotating into an overhang afterwards. Positions
+    // that several paths lead to are only kept with the shortest one.
+    pub fn placements(&self) -> Vec<Placement> {
+        let mut visited: HashSet<(Rotation, Vector2D)> = HashSet::new();
+        let mut landed: HashSet<Vec<(i32, i32)>> = HashSet::new();
+        let mut queue = VecDeque::from([(self.current.clone(), Vec::new())]);
+        let mut placements: Vec<Placement> = Vec::new();
+
+        visited.insert(key(&self.current));
+
+        while let Some((tetromino, path)) = queue.pop_front() {
+            let placement = self.landed(&tetromino, &path);
+            if landed.insert(placement.squares()) {
+                placements.push(placement);
+            }
+
+            for input in MOVES {
+                let Some(moved) = self.moved(&tetromino, input) else {
+                    continue;
+                };
+
+                if visited.insert(key(&moved)) {
+                    let mut path = path.clone();
+                    path.push(input);
+                    queue.push_back((moved, path));
+                }
+            }
+        }
+
+        placements
+    }
+
+    // The placement that covers exactly the given sorted squares, searched from wherever the piece
+    // is now. Gravity pulls the piece down while a path is played, so the path is planned again
+    // before every input instead of soft dropping to a height the piece may already be below.
+    pub fn placement_at(&self, squares: &[(i32, i32)]) -> Option<Placement> {
+        self.placements()
+            .into_iter()
+            .find(|placement| placement.squares() == squares)
+    }
+
+    fn landed(&self, tetromino: &Tetromino, path: &[Input]) -> Placement {
+        let mut landed = tetromino.clone();
+        while let Some(moved) = self.moved(&landed, Input::Down) {
+            landed = moved;
+        }
+
+        let mut path = path.to_vec();
+        path.push(Input::Drop);
+
+        Placement {
+            tetromino: landed,
+            path,
+        }
+    }
+}
+
+fn key(tetromino: &Tetromino) -> (Rotation, Vector2D) {
+    (tetromino.rotation.clone(), tetromino.coords.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mode::Mode,
+        state::{Square, FIELD_HEIGHT, FIELD_WIDTH},
+        tetromino::Kind,
+    };
+
+    use super::*;
+
+    const KINDS: [Kind; 7] = [
+        Kind::I,
+        Kind::O,
+        Kind::T,
+        Kind::S,
+        Kind::Z,
+        Kind::J,
+        Kind::L,
+    ];
+
+    fn state(kind: Kind) -> GameState {
+        let mut state = GameState::seeded(0, Mode::Marathon, 0);
+        state.current = Tetromino::new(kind);
+
+        state
+    }
+
+    #[test]
+    fn every_column_and_rotation_on_an_empty_field() {
+        let counts: Vec<usize> = KINDS.map(|x| state(x).placements().len()).to_vec();
+
+        assert_eq!(counts, [17, 9, 34, 17, 17, 34, 34]);
+    }
+
+    #[test]
+    fn placements_are_distinct() {
+        for kind in KINDS {
+            let placements = state(kind).placements();
+            let landed: HashSet<Vec<(i32, i32)>> = placements.iter().map(|x| x.squares()).collect();
+
+            assert_eq!(landed.len(), placements.len());
+        }
+    }
+
+    // The paths are played with the moves of the game, the piece lands where it was planned to
+    #[test]
+    fn paths_lead_to_their_placement() {
+        for kind in KINDS {
+            let state = state(kind);
+
+            for placement in state.placements() {
+                let (drop, moves) = placement.path.split_last().unwrap();
+                assert_eq!(*drop, Input::Drop);
+
+                let mut played = state.clone();
+                for input in moves {
+                    match input {
+                        Input::Left => played.move_left(),
+                        Input::Right => played.move_right(),
+                        Input::Rotate => played.rotate(),
+                        Input::Down => assert!(!played.move_down()),
+                        input => panic!("unexpected {input:?} in a path"),
+                    }
+                }
+
+                let preview = played.determine_preview().unwrap();
+                let squares = Placement {
+                    tetromino: preview,
+                    path: Vec::new(),
+                }
+                .squares();
+
+                assert_eq!(squares, placement.squares());
+            }
+        }
+    }
+
+    // The only way under a roof is to soft drop next to it and slide in
+    #[test]
+    fn tucks_under_an_overhang() {
+        let mut state = state(Kind::I);
+        let roof = FIELD_HEIGHT - 2;
+        for x in 0..FIELD_WIDTH / 2 {
+            state.field[roof][x] = Square::Garbage;
+        }
+
+        let tuck = state
+            .placements()
+            .into_iter()
+            .find(|placement| {
+                placement
+                    .squares()
+                    .iter()
+                    .all(|&(x, y)| y > roof as i32 && x < (FIELD_WIDTH / 2) as i32)
+            })
+            .expect("a tuck under the roof");
+
+        assert!(tuck.path.contains(&Input::Down));
+        assert_eq!(state.placement_at(&tuck.squares()), Some(tuck));
+    }
+}
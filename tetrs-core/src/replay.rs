@@ -0,0 +1,158 @@
+use serde::{de::IgnoredAny, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    input::Input,
+    mode::Mode,
+    script::Script,
+    state::{GameState, TICKS_PER_SECOND},
+};
+
+// Everything needed to play a single player game again exactly as it happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub mode: Mode,
+    pub level: u32,
+    #[serde(default = "ticks_per_second")]
+    pub tick_rate: u32,
+    pub ticks: u64,
+    // The pieces placed by the end of the recording, so that it can be seeked without playing it
+    #[serde(default)]
+    pub pieces: u32,
+    #[serde(deserialize_with = "inputs")]
+    pub inputs: Vec<(u64, Input)>,
+}
+
+impl Replay {
+    // Starts recording a game that has not been played yet
+    pub fn new(state: &GameState) -> Self {
+        Self {
+            seed: state.seed,
+            mode: state.mode,
+            level: state.level.start,
+            tick_rate: state.tick_rate,
+            ticks: 0,
+            pieces: 0,
+            inputs: Vec::new(),
+        }
+    }
+
+    // Records the inputs handed to the game in one tick and the state the tick left it in
+    pub fn record(&mut self, inputs: &[Input], state: &GameState) {
+        self.inputs
+            .extend(inputs.iter().map(|&input| (self.ticks, input)));
+        self.ticks += 1;
+        self.pieces = state.pieces;
+    }
+
+    pub fn state(&self) -> GameState {
+        let mut state = GameState::seeded(self.level, self.mode, self.seed);
+        state.tick_rate = self.tick_rate;
+
+        state
+    }
+
+    pub fn script(&self) -> Script {
+        Script::new(self.inputs.iter().copied())
+    }
+}
+
+// Replays saved before the tick rate could be configured were all played at the default rate
+fn ticks_per_second() -> u32 {
+    TICKS_PER_SECOND
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Recorded {
+    Input(Input),
+    Other(IgnoredAny),
+}
+
+// Older replays also recorded keys of the frontend like pause, which never changed the game
+fn inputs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(u64, Input)>, D::Error> {
+    let recorded: Vec<(u64, Recorded)> = Vec::deserialize(deserializer)?;
+
+    Ok(recorded
+        .into_iter()
+        .filter_map(|(tick, recorded)| match recorded {
+            Recorded::Input(input) => Some((tick, input)),
+            Recorded::Other(_) => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::swap;
+
+    use super::*;
+
+    // The moves and gravity of one tick, without the scoring and garbage of the frontend
+    fn tick(state: &mut GameState, inputs: &[Input]) {
+        let solidified = match inputs.first() {
+            Some(Input::Left) => {
+                state.move_left();
+                false
+            }
+            Some(Input::Right) => {
+                state.move_right();
+                false
+            }
+            Some(Input::Rotate) => {
+                state.rotate();
+                false
+            }
+            Some(Input::Down) => state.move_down(),
+            Some(Input::Drop) => state.drop(),
+            None => false,
+        };
+
+        if state.advance_game(solidified) {
+            let cleared_lines = state.clear_lines();
+            state.level.up(&cleared_lines);
+
+            swap(&mut state.current, &mut state.next);
+            state.next = state.deal_tetromino();
+            state.pieces += 1;
+        }
+    }
+
+    #[test]
+    fn replays_the_recorded_game() {
+        let mut state = GameState::seeded(3, Mode::Marathon, 42);
+        let mut script = Script::new([
+            (5, Input::Left),
+            (6, Input::Left),
+            (10, Input::Drop),
+            (20, Input::Rotate),
+            (21, Input::Right),
+            (30, Input::Drop),
+            (40, Input::Down),
+            (41, Input::Down),
+            (90, Input::Right),
+        ]);
+        let mut replay = Replay::new(&state);
+        let mut played = Vec::new();
+
+        for _ in 0..300 {
+            let inputs = script.next_inputs();
+            tick(&mut state, &inputs);
+            replay.record(&inputs, &state);
+            played.push(state.clone());
+        }
+
+        let mut replayed = replay.state();
+        let mut script = replay.script();
+
+        // Every tick of the replay matches the game, not only the end
+        for state in &played {
+            tick(&mut replayed, &script.next_inputs());
+            assert_eq!(&replayed, state);
+        }
+
+        assert_eq!(replay.ticks, played.len() as u64);
+        assert_eq!(replay.pieces, state.pieces);
+        assert!(state.pieces >= 2);
+    }
+}
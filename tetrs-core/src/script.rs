@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+use crate::input::Input;
+
+// Inputs stamped with the tick they are played in, counted from the start of the game
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Script {
+    tick: u64,
+    inputs: VecDeque<(u64, Input)>,
+    quit: Option<u64>,
+}
+
+impl Script {
+    pub fn new(inputs: impl IntoIterator<Item = (u64, Input)>) -> Self {
+        let mut inputs: VecDeque<(u64, Input)> = inputs.into_iter().collect();
+        inputs.make_contiguous().sort_by_key(|&(tick, _)| tick);
+
+        Self {
+            tick: 0,
+            inputs,
+            quit: None,
+        }
+    }
+
+    // Reads one "<tick> <input>" pair per line, empty lines and lines starting with # are skipped.
+    // The input quit ends the game at its tick.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut inputs = Vec::new();
+        let mut quit: Option<u64> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |reason: String| format!("Line {}: {reason}", index + 1);
+
+            let Some((tick, input)) = line.split_once(char::is_whitespace) else {
+                return Err(error(String::from("Expected a tick and an input")));
+            };
+            let tick: u64 = tick
+                .parse()
+                .map_err(|_| error(format!("Invalid tick {tick}")))?;
+
+            match input.trim() {
+                "quit" => quit = Some(quit.map_or(tick, |x| x.min(tick))),
+                input => inputs.push((tick, input.parse().map_err(error)?)),
+            }
+        }
+
+        Ok(Self {
+            quit,
+            ..Self::new(inputs)
+        })
+    }
+
+    // True once the tick the script quits at has been played
+    pub fn is_over(&self) -> bool {
+        self.quit.map_or(false, |tick| self.tick > tick)
+    }
+
+    // Returns the inputs of the current tick and advances to the next one
+    pub fn next_inputs(&mut self) -> Vec<Input> {
+        let mut inputs = Vec::new();
+
+        while let Some(&(tick, input)) = self.inputs.front() {
+            if tick > self.tick {
+                break;
+            }
+
+            inputs.push(input);
+            self.inputs.pop_front();
+        }
+
+        self.tick += 1;
+
+        inputs
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::input::Input;
+
+use super::{
+    garbage::{Attack, Garbage, Spin},
+    level::{ClearedLines, Level},
+    mode::Mode,
+    stats::Stats,
+    tetromino::{Kind, Tetromino},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Square {
+    Empty,
+    Occupied(Kind),
+    Garbage,
+}
+
+pub const FIELD_HEIGHT: usize = 20;
+pub const FIELD_WIDTH: usize = 10;
+
+// How many pieces are dealt in advance, the next one included
+pub const NEXT_PIECES: usize = 3;
+
+// The gravity of every level is given in ticks, games are played at this rate unless configured
+pub const TICKS_PER_SECOND: u32 = 60;
+
+pub type Field = VecDeque<[Square; FIELD_WIDTH]>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameState {
+    pub mode: Mode,
+    pub level: Level,
+    pub current: Tetromino,
+    pub next: Tetromino,
+    pub queue: VecDeque<Tetromino>,
+    pub preview: Option<Tetromino>,
+    pub ticks: u32,
+    pub time: u64,
+    // The ticks per second the game is played at, which its time is counted in
+    #[serde(default = "ticks_per_second")]
+    pub tick_rate: u32,
+    pub pieces: u32,
+    pub field: Field,
+    pub garbage: Garbage,
+    pub attack: Attack,
+    pub outgoing: u32,
+    pub rotated: bool,
+    // Set once garbage pushed blocks out of the top of the field
+    #[serde(default)]
+    pub topped_out: bool,
+    pub stats: Stats,
+    pub seed: u64,
+    random: ChaCha8Rng,
+}
+
+impl GameState {
+    pub fn new(level: u32, mode: Mode) -> Self {
+        Self::seeded(level, mode, rand::random())
+    }
+
+    // The same seed always deals the same pieces and garbage holes
+    pub fn seeded(level: u32, mode: Mode, seed: u64) -> Self {
+        let mut random = ChaCha8Rng::seed_from_u64(seed);
+
+        let level = match mode.is_endless() {
+            true => Level::fixed(level),
+            false => Level::new(level),
+        };
+
+        let mut state = Self {
+            mode,
+            level,
+            current: Tetromino::random(&mut random),
+            next: Tetromino::random(&mut random),
+            queue: (1..NEXT_PIECES)
+                .map(|_| Tetromino::random(&mut random))
+                .collect(),
+            preview: None,
+            ticks: 0,
+            time: 0,
+            tick_rate: TICKS_PER_SECOND,
+            pieces: 0,
+            field: VecDeque::from(vec![[Square::Empty; 10]; 20]),
+            garbage: Garbage::default(),
+            attack: Attack::default(),
+            outgoing: 0,
+            rotated: false,
+            topped_out: false,
+            stats: Stats::default(),
+            seed,
+            random,
+        };
+
+        state.preview = state.determine_preview();
+
+        state
+    }
+}
+
+fn ticks_per_second() -> u32 {
+    TICKS_PER_SECOND
+}
+
+impl GameState {
+    pub fn determine_preview(&self) -> Option<Tetromino> {
+        let mut preview = self.current.clone();
+
+        preview.move_down();
+
+        if self.check_collision(&preview).is_some() {
+            return None;
+        }
+
+        while self.check_collision(&preview).is_none() {
+            preview.move_down();
+        }
+
+        preview.move_up();
+
+        Some(preview)
+    }
+
+    pub fn try_move_down(&mut self) -> bool {
+        match self.try_solidify() {
+            true => true,
+            false => {
+                self.current.move_down();
+                self.rotated = false;
+                false
+            }
+        }
+    }
+
+    fn try_solidify(&mut self) -> bool {
+        let mut copy = self.current.clone();
+        copy.move_down();
+
+        if self.check_collision(&copy).is_some() {
+            for elem in self.current.offset_blocks().iter() {
+                self.field[elem.vec.y as usize][elem.vec.x as usize] =
+                    Square::Occupied(self.current.kind);
+            }
+
+            return true;
+        }
+
+        false
+    }
+
+    pub fn check_collision(&self, tetromino: &Tetromino) -> Option<Collision> {
+        if Self::is_out_of_bounds(tetromino) {
+            return Some(Collision::OutOfBounds);
+        }
+
+        if Self::has_collision_with_block(tetromino, &self.field) {
+            return Some(Collision::WithBlock);
+        }
+
+        None
+    }
+
+    fn has_collision_with_block(tetromino: &Tetromino, field: &Field) -> bool {
+        tetromino.offset_blocks().iter().any(|block| {
+            match &field[block.vec.y as usize][block.vec.x as usize] {
+                Square::Empty => false,
+                Square::Occupied(_) | Square::Garbage => true,
+            }
+        })
+    }
+
+    fn is_out_of_bounds(tetromino: &Tetromino) -> bool {
+        tetromino.offset_blocks().iter().any(|block| {
+            block.vec.x as usize >= FIELD_WIDTH
+                || block.vec.x < 0
+                || block.vec.y as usize >= FIELD_HEIGHT
+                || block.vec.y < 0
+        })
+    }
+
+    pub fn clear_lines(&mut self) -> ClearedLines {
+        self.field
+            .retain(|line| line.iter().any(|square| square == &Square::Empty));
+
+        let cleared_lines = FIELD_HEIGHT - self.field.len();
+
+        while self.field.len() < FIELD_HEIGHT {
+            self.field.push_front([Square::Empty; 10]);
+        }
+
+        cleared_lines.into()
+    }
+
+    pub fn rotate(&mut self) {
+        if let Some(rotated) = self.moved(&self.current, Input::Rotate) {
+            self.current = rotated;
+            self.rotated = true;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(moved) = self.moved(&self.current, Input::Right) {
+            self.current = moved;
+            self.rotated = false;
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(moved) = self.moved(&self.current, Input::Left) {
+            self.current = moved;
+            self.rotated = false;
+        }
+    }
+
+    // Applies a single movement to a copy of the tetromino, None if the movement is not legal
+    pub fn moved(&self, tetromino: &Tetromino, input: Input) -> Option<Tetromino> {
+        let mut moved = tetromino.clone();
+
+        match input {
+            Input::Left => moved.move_left(),
+            Input::Right => moved.move_right(),
+            Input::Down => moved.move_down(),
+            Input::Rotate => moved.rotate(),
+            _ => return None,
+        }
+
+        match self.check_collision(&moved) {
+            Some(_) => None,
+            None => Some(moved),
+        }
+    }
+
+    pub fn move_down(&mut self) -> bool {
+        self.try_move_down()
+    }
+
+    pub fn drop(&mut self) -> bool {
+        while !self.try_move_down() {}
+
+        true
+    }
+
+    pub fn advance_game(&mut self, already_solidified: bool) -> bool {
+        self.ticks += 1;
+        self.time += 1;
+
+        match self.ticks > self.level.required_ticks() {
+            true => {
+                self.ticks = 0;
+                already_solidified || self.try_move_down()
+            }
+            false => already_solidified,
+        }
+    }
+
+    // Takes the first piece of the queue and refills it
+    pub fn deal_tetromino(&mut self) -> Tetromino {
+        let tetromino = Tetromino::random(&mut self.random);
+        self.queue.push_back(tetromino);
+
+        self.queue.pop_front().expect("The queue is never empty")
+    }
+
+    pub fn upcoming(&self) -> impl Iterator<Item = &Tetromino> {
+        std::iter::once(&self.next).chain(self.queue.iter())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.topped_out || self.check_collision(&self.current).is_some()
+    }
+
+    // Pushes all pending garbage lines in from the bottom, every attack gets its own hole
+    pub fn receive_garbage(&mut self) {
+        for lines in self.garbage.release() {
+            let hole = self.random.gen_range(0..FIELD_WIDTH);
+
+            for _ in 0..lines {
+                let mut line = [Square::Garbage; FIELD_WIDTH];
+                line[hole] = Square::Empty;
+
+                let top = self.field.pop_front();
+                self.field.push_back(line);
+
+                if top.map_or(false, |x| x.iter().any(|&square| square != Square::Empty)) {
+                    self.topped_out = true;
+                }
+            }
+        }
+    }
+
+    // Uses the three corner rule: a T that got into place by rotating with at least three of the
+    // four squares diagonal to its center blocked
+    pub fn spin(&self) -> Spin {
+        if !self.rotated || self.current.kind != Kind::T {
+            return Spin::None;
+        }
+
+        let center = self.current.center();
+
+        let blocked = [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+            .iter()
+            .filter(|(x, y)| self.is_blocked(center.x + x, center.y + y))
+            .count();
+
+        match blocked >= 3 {
+            true => Spin::TSpin,
+            false => Spin::None,
+        }
+    }
+
+    fn is_blocked(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x as usize >= FIELD_WIDTH || y as usize >= FIELD_HEIGHT {
+            return true;
+        }
+
+        self.field[y as usize][x as usize] != Square::Empty
+    }
+
+    // The field as it would look like after the tetromino was placed and full lines were cleared,
+    // together with the number of cleared lines
+    pub fn place(&self, tetromino: &Tetromino) -> (Field, usize) {
+        let mut field = self.field.clone();
+
+        for block in tetromino.offset_blocks().iter() {
+            field[block.vec.y as usize][block.vec.x as usize] = Square::Occupied(tetromino.kind);
+        }
+
+        field.retain(|line| line.iter().any(|square| square == &Square::Empty));
+        let cleared_lines = FIELD_HEIGHT - field.len();
+
+        while field.len() < FIELD_HEIGHT {
+            field.push_front([Square::Empty; FIELD_WIDTH]);
+        }
+
+        (field, cleared_lines)
+    }
+
+    pub fn is_perfect_clear(&self) -> bool {
+        self.field
+            .iter()
+            .all(|line| line.iter().all(|square| square == &Square::Empty))
+    }
+
+    pub fn clear_field(&mut self) {
+        for line in self.field.iter_mut() {
+            *line = [Square::Empty; FIELD_WIDTH];
+        }
+
+        self.topped_out = false;
+    }
+}
+
+pub enum Collision {
+    OutOfBounds,
+    WithBlock,
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::Input,
+    level::ClearedLines,
+    placement::Placement,
+    state::{GameState, Square},
+    tetromino::{Kind, Tetromino},
+};
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub keys: u32,
+    pub attack: u32,
+    // Singles, doubles, triples and tetrises
+    pub clears: [u32; 4],
+    // Placed pieces of every kind in the order I, O, T, S, Z, J, L
+    pub kinds: [u32; 7],
+    // Pieces that were placed with more inputs than necessary
+    pub faults: u32,
+    piece_keys: u32,
+}
+
+impl Stats {
+    // Soft drops are up to the player and never a fault
+    pub fn key(&mut self, input: &Input) {
+        self.keys += 1;
+
+        if *input != Input::Down {
+            self.piece_keys += 1;
+        }
+    }
+
+    pub fn placed(&mut self, kind: Kind, minimal_inputs: Option<usize>) {
+        self.kinds[kind as usize] += 1;
+
+        if minimal_inputs.map_or(false, |x| self.piece_keys as usize > x) {
+            self.faults += 1;
+        }
+
+        self.piece_keys = 0;
+    }
+
+    pub fn cleared(&mut self, lines: &ClearedLines, attack: u32) {
+        let index = match lines {
+            ClearedLines::None => None,
+            ClearedLines::Single => Some(0),
+            ClearedLines::Double => Some(1),
+            ClearedLines::Triple => Some(2),
+            ClearedLines::Tetrs => Some(3),
+        };
+
+        if let Some(index) = index {
+            self.clears[index] += 1;
+        }
+
+        self.attack += attack;
+    }
+}
+
+impl GameState {
+    pub fn seconds(&self) -> f64 {
+        self.time as f64 / self.tick_rate as f64
+    }
+
+    pub fn pieces_per_second(&self) -> f64 {
+        rate(self.pieces as f64, self.seconds())
+    }
+
+    pub fn keys_per_piece(&self) -> f64 {
+        rate(self.stats.keys as f64, self.pieces as f64)
+    }
+
+    pub fn attack_per_minute(&self) -> f64 {
+        rate(self.stats.attack as f64, self.seconds() / 60.0)
+    }
+
+    // The fewest inputs that move a freshly spawned piece to where the current one has just been
+    // locked, None if that place cannot be reached from the spawn. Soft drops are not counted.
+    pub fn minimal_inputs(&self) -> Option<usize> {
+        let locked = Placement {
+            tetromino: self.current.clone(),
+            path: Vec::new(),
+        };
+
+        let mut spawned = self.clone();
+        for block in self.current.offset_blocks().iter() {
+            spawned.field[block.vec.y as usize][block.vec.x as usize] = Square::Empty;
+        }
+        spawned.current = Tetromino::new(self.current.kind);
+
+        spawned
+            .placements()
+            .into_iter()
+            .find(|x| x.squares() == locked.squares())
+            .map(|x| x.path.iter().filter(|&&x| x != Input::Down).count())
+    }
+}
+
+fn rate(count: f64, per: f64) -> f64 {
+    match per > 0.0 {
+        true => count / per,
+        false => 0.0,
+    }
+}
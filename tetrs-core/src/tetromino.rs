@@ -1,23 +1,34 @@
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
-use tui::style::Color;
 
 use super::math::{Matrix2D, Vector2D, ROTATE_CCW, ROTATE_CW};
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Kind {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Rotation {
     None,
     Two(Two),
     Four(Four),
 }
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Two {
     Right,
     Up,
 }
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Four {
     Right,
     Up,
@@ -25,7 +36,7 @@ pub enum Four {
     Down,
 }
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Block {
     pub vec: Vector2D,
 }
@@ -38,13 +49,13 @@ impl Block {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tetromino {
+    pub kind: Kind,
     pub blocks: [Block; 4],
     pub rotation: Rotation,
     pub origin: Block,
     pub coords: Vector2D,
-    pub color: Color,
 }
 
 impl Hash for Tetromino {
@@ -58,6 +69,7 @@ impl Hash for Tetromino {
 impl Tetromino {
     pub const fn i() -> Tetromino {
         Tetromino {
+            kind: Kind::I,
             blocks: [
                 Block::new(0, 1),
                 Block::new(1, 1),
@@ -66,13 +78,13 @@ impl Tetromino {
             ],
             rotation: Rotation::Two(Two::Right),
             origin: Block::new(2, 1),
-            color: Color::Cyan,
             coords: Vector2D::default(),
         }
     }
 
     pub const fn o() -> Tetromino {
         Tetromino {
+            kind: Kind::O,
             blocks: [
                 Block::new(1, 1),
                 Block::new(2, 1),
@@ -81,13 +93,13 @@ impl Tetromino {
             ],
             rotation: Rotation::None,
             origin: Block::new(2, 1),
-            color: Color::Yellow,
             coords: Vector2D::default(),
         }
     }
 
     pub const fn t() -> Tetromino {
         Tetromino {
+            kind: Kind::T,
             blocks: [
                 Block::new(0, 1),
                 Block::new(1, 1),
@@ -96,13 +108,13 @@ impl Tetromino {
             ],
             rotation: Rotation::Four(Four::Right),
             origin: Block::new(1, 1),
-            color: Color::Magenta,
             coords: Vector2D::default(),
         }
     }
 
     pub const fn s() -> Tetromino {
         Tetromino {
+            kind: Kind::S,
             blocks: [
                 Block::new(1, 1),
                 Block::new(2, 1),
@@ -111,13 +123,13 @@ impl Tetromino {
             ],
             rotation: Rotation::Two(Two::Right),
             origin: Block::new(1, 1),
-            color: Color::Green,
             coords: Vector2D::default(),
         }
     }
 
     pub const fn z() -> Tetromino {
         Tetromino {
+            kind: Kind::Z,
             blocks: [
                 Block::new(0, 1),
                 Block::new(1, 1),
@@ -126,13 +138,13 @@ impl Tetromino {
             ],
             rotation: Rotation::Two(Two::Right),
             origin: Block::new(1, 1),
-            color: Color::Red,
             coords: Vector2D::default(),
         }
     }
 
     pub const fn j() -> Tetromino {
         Tetromino {
+            kind: Kind::J,
             blocks: [
                 Block::new(0, 1),
                 Block::new(1, 1),
@@ -141,13 +153,13 @@ impl Tetromino {
             ],
             rotation: Rotation::Four(Four::Right),
             origin: Block::new(1, 1),
-            color: Color::Blue,
             coords: Vector2D::default(),
         }
     }
 
     pub const fn l() -> Tetromino {
         Tetromino {
+            kind: Kind::L,
             blocks: [
                 Block::new(0, 1),
                 Block::new(1, 1),
@@ -156,13 +168,12 @@ impl Tetromino {
             ],
             rotation: Rotation::Four(Four::Right),
             origin: Block::new(1, 1),
-            color: Color::White,
             coords: Vector2D::default(),
         }
     }
 
-    pub fn next() -> Tetromino {
-        match rand::thread_rng().gen_range(0..7) {
+    pub fn random<R: Rng>(random: &mut R) -> Tetromino {
+        match random.gen_range(0..7) {
             0 => Tetromino::i(),
             1 => Tetromino::o(),
             2 => Tetromino::t(),
@@ -174,6 +185,18 @@ impl Tetromino {
         }
     }
 
+    pub const fn new(kind: Kind) -> Tetromino {
+        match kind {
+            Kind::I => Tetromino::i(),
+            Kind::O => Tetromino::o(),
+            Kind::T => Tetromino::t(),
+            Kind::S => Tetromino::s(),
+            Kind::Z => Tetromino::z(),
+            Kind::J => Tetromino::j(),
+            Kind::L => Tetromino::l(),
+        }
+    }
+
     pub fn move_right(&mut self) {
         self.coords.x += 1;
     }
@@ -221,6 +244,13 @@ impl Tetromino {
         }
     }
 
+    pub const fn center(&self) -> Vector2D {
+        Vector2D {
+            x: self.origin.vec.x + self.coords.x,
+            y: self.origin.vec.y + self.coords.y,
+        }
+    }
+
     pub const fn offset_blocks(&self) -> [Block; 4] {
         [
             self.offset_block(0),